@@ -0,0 +1,150 @@
+//! DACL evaluation for the `--check-acls` and `--check-binary-perms` audits.
+//!
+//! A security descriptor is considered writable by standard users when an ACE for one of the
+//! groups every standard user belongs to grants a write right that no deny ACE for the same
+//! group takes away. This is a static check of the DACL, not an `AccessCheck` against a token.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use windows::core::HSTRING;
+use windows::Win32::Foundation::{BOOL, PSID};
+use windows::Win32::Security::{
+    AclSizeInformation, CreateWellKnownSid, EqualSid, GetAce, GetAclInformation,
+    GetFileSecurityW, GetSecurityDescriptorDacl, WinAuthenticatedUserSid, WinBuiltinUsersSid,
+    WinInteractiveSid, WinWorldSid, ACCESS_ALLOWED_ACE, ACL, ACL_SIZE_INFORMATION,
+    DACL_SECURITY_INFORMATION, INHERIT_ONLY_ACE, PSECURITY_DESCRIPTOR, WELL_KNOWN_SID_TYPE,
+};
+
+use crate::server_path::server_binary;
+use crate::types::ComObject;
+
+/// ACE types, from `winnt.h`
+const ACCESS_ALLOWED_ACE_TYPE: u8 = 0;
+const ACCESS_DENIED_ACE_TYPE: u8 = 1;
+
+/// Largest possible SID, from `winnt.h`
+const SECURITY_MAX_SID_SIZE: usize = 68;
+
+/// Generic rights that imply write access before they are mapped to object rights
+const GENERIC_WRITE_RIGHTS: u32 = 0x4000_0000 | 0x1000_0000;
+
+/// Rights on a directory that let the holder plant or replace a file in it: `FILE_ADD_FILE`,
+/// `FILE_DELETE_CHILD`, `WRITE_DAC` and `WRITE_OWNER`
+const DIRECTORY_WRITE_RIGHTS: u32 = 0x0002 | 0x0040 | 0x0004_0000 | 0x0008_0000;
+
+/// Groups that every standard (non-admin) user is a member of
+const LOW_PRIVILEGE_GROUPS: [WELL_KNOWN_SID_TYPE; 4] = [
+    WinWorldSid,
+    WinAuthenticatedUserSid,
+    WinBuiltinUsersSid,
+    WinInteractiveSid,
+];
+
+/// Returns true if the DACL of the security descriptor lets a low-privileged group exercise
+/// any of the `write_rights`. A missing (NULL) DACL grants everyone full access.
+///
+/// # Safety
+/// `descriptor` must point to a valid self-relative or absolute security descriptor.
+pub unsafe fn grants_write_to_users(
+    descriptor: PSECURITY_DESCRIPTOR,
+    write_rights: u32,
+) -> Result<bool> {
+    let mut present = BOOL::default();
+    let mut defaulted = BOOL::default();
+    let mut dacl: *mut ACL = std::ptr::null_mut();
+    GetSecurityDescriptorDacl(descriptor, &mut present, &mut dacl, &mut defaulted)?;
+    if !present.as_bool() || dacl.is_null() {
+        return Ok(true);
+    }
+
+    let mut info = ACL_SIZE_INFORMATION::default();
+    GetAclInformation(
+        dacl,
+        &mut info as *mut _ as *mut _,
+        std::mem::size_of::<ACL_SIZE_INFORMATION>() as u32,
+        AclSizeInformation,
+    )?;
+
+    let write_rights = write_rights | GENERIC_WRITE_RIGHTS;
+    for group in LOW_PRIVILEGE_GROUPS {
+        let mut sid = [0u8; SECURITY_MAX_SID_SIZE];
+        let mut sid_size = sid.len() as u32;
+        let group_sid = PSID(sid.as_mut_ptr() as *mut _);
+        CreateWellKnownSid(group, PSID::default(), group_sid, &mut sid_size)?;
+
+        let mut allowed = 0u32;
+        let mut denied = 0u32;
+        for index in 0..info.AceCount {
+            let mut ace: *mut core::ffi::c_void = std::ptr::null_mut();
+            GetAce(dacl, index, &mut ace)?;
+
+            // Allowed and denied ACEs share the layout: header, mask, then the SID
+            let ace = &*(ace as *const ACCESS_ALLOWED_ACE);
+            if u32::from(ace.Header.AceFlags) & INHERIT_ONLY_ACE.0 != 0 {
+                continue;
+            }
+            let ace_sid = PSID(&ace.SidStart as *const u32 as *mut _);
+            if EqualSid(ace_sid, group_sid).is_err() {
+                continue;
+            }
+            match ace.Header.AceType {
+                ACCESS_ALLOWED_ACE_TYPE => allowed |= ace.Mask,
+                ACCESS_DENIED_ACE_TYPE => denied |= ace.Mask,
+                _ => {}
+            }
+        }
+
+        if allowed & !denied & write_rights != 0 {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Sets `binary_hijackable` on every object whose `InprocServer32` or `LocalServer32` binary
+/// lies in a directory that standard users can write to. Each directory is checked once.
+pub fn check_binary_perms(objects: &mut HashMap<String, ComObject>) {
+    let mut directories: HashMap<PathBuf, bool> = HashMap::new();
+    for obj in objects.values_mut() {
+        let servers = [&obj.inproc_server, &obj.local_server];
+        obj.binary_hijackable = servers.into_iter().flatten().any(|server| {
+            // Bare file names are resolved through the search path, not a known directory
+            let Some(dir) = server_binary(server).and_then(|path| parent_directory(&path)) else {
+                return false;
+            };
+            *directories
+                .entry(dir)
+                .or_insert_with_key(|dir| directory_writable_by_users(dir))
+        });
+    }
+}
+
+/// The directory containing an absolute binary path
+fn parent_directory(path: &Path) -> Option<PathBuf> {
+    if !path.is_absolute() {
+        return None;
+    }
+    path.parent().map(Path::to_path_buf)
+}
+
+/// Reads the DACL of a directory and checks it for write access by standard users.
+/// A directory that does not exist or cannot be read is reported as not writable.
+fn directory_writable_by_users(dir: &Path) -> bool {
+    let name = HSTRING::from(dir.to_string_lossy().as_ref());
+    let information = DACL_SECURITY_INFORMATION.0;
+    unsafe {
+        let mut size = 0u32;
+        let _ = GetFileSecurityW(&name, information, PSECURITY_DESCRIPTOR::default(), 0, &mut size);
+        if size == 0 {
+            return false;
+        }
+
+        let mut buffer = vec![0u8; size as usize];
+        let descriptor = PSECURITY_DESCRIPTOR(buffer.as_mut_ptr() as *mut _);
+        if !GetFileSecurityW(&name, information, descriptor, size, &mut size).as_bool() {
+            return false;
+        }
+        grants_write_to_users(descriptor, DIRECTORY_WRITE_RIGHTS).unwrap_or(false)
+    }
+}
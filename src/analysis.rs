@@ -0,0 +1,329 @@
+//! Consistency checks over a completed scan.
+//!
+//! These passes look at the merged results as a whole to find registrations that contradict
+//! each other, such as one ProgID claimed by several CLSIDs or one CLSID behind several
+//! ProgIDs, or to summarize them, such as the `--fingerprint` digest and the statistics of
+//! [`compute_summary`].
+
+use anyhow::Result;
+use serde::{Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+
+use crate::types::ComObject;
+
+/// Bucket for objects whose key function returns nothing
+pub const UNKNOWN_GROUP: &str = "Unknown";
+
+/// Group the results by ProgID (ignoring case, as the registry does) and return every ProgID
+/// claimed by more than one CLSID, with its CLSIDs sorted. The list is sorted by ProgID.
+pub fn duplicate_prog_ids(objects: &HashMap<String, ComObject>) -> Vec<(String, Vec<String>)> {
+    let mut groups: HashMap<String, (String, Vec<String>)> = HashMap::new();
+    for obj in objects.values() {
+        if let Some(ref prog_id) = obj.prog_id {
+            groups
+                .entry(prog_id.to_lowercase())
+                .or_insert_with(|| (prog_id.clone(), Vec::new()))
+                .1
+                .push(obj.clsid.clone());
+        }
+    }
+
+    let mut duplicates: Vec<(String, Vec<String>)> = groups
+        .into_values()
+        .filter(|(_, clsids)| clsids.len() > 1)
+        .map(|(prog_id, mut clsids)| {
+            clsids.sort();
+            (prog_id, clsids)
+        })
+        .collect();
+    duplicates.sort_by_key(|(prog_id, _)| prog_id.to_lowercase());
+    duplicates
+}
+
+/// Every scanned CLSID that more than one ProgID points back to through its `CLSID` subkey,
+/// with those ProgIDs (distinct ignoring case, sorted). The list is sorted by CLSID.
+/// `index` holds `(ProgID, CLSID)` pairs, possibly repeated across registry views.
+pub fn multi_prog_ids(
+    objects: &HashMap<String, ComObject>,
+    index: &[(String, String)],
+) -> Vec<(String, Vec<String>)> {
+    let mut by_clsid: HashMap<String, Vec<String>> = HashMap::new();
+    for (prog_id, clsid) in index {
+        let prog_ids = by_clsid.entry(clsid.to_uppercase()).or_default();
+        if !prog_ids.iter().any(|known| known.eq_ignore_ascii_case(prog_id)) {
+            prog_ids.push(prog_id.clone());
+        }
+    }
+
+    let mut multi: Vec<(String, Vec<String>)> = objects
+        .values()
+        .filter_map(|obj| {
+            let prog_ids = by_clsid.get(&obj.clsid.to_uppercase())?;
+            (prog_ids.len() > 1).then(|| (obj.clsid.clone(), prog_ids.clone()))
+        })
+        .map(|(clsid, mut prog_ids)| {
+            prog_ids.sort_by_key(|prog_id| prog_id.to_lowercase());
+            (clsid, prog_ids)
+        })
+        .collect();
+    multi.sort();
+    multi
+}
+
+/// Group objects by the key returned for each one, such as their vendor, ignoring case.
+/// Objects without a key go into [`UNKNOWN_GROUP`]. Groups are sorted from largest to
+/// smallest, then by name, with the unknown group last; objects keep their input order.
+pub fn group_by<'a>(
+    objects: impl IntoIterator<Item = &'a ComObject>,
+    key: impl Fn(&ComObject) -> Option<&str>,
+) -> Vec<(String, Vec<&'a ComObject>)> {
+    let mut groups: HashMap<String, (String, Vec<&'a ComObject>)> = HashMap::new();
+    let mut unknown = Vec::new();
+    for obj in objects {
+        match key(obj) {
+            Some(name) => groups
+                .entry(name.to_lowercase())
+                .or_insert_with(|| (name.to_string(), Vec::new()))
+                .1
+                .push(obj),
+            None => unknown.push(obj),
+        }
+    }
+
+    let mut groups: Vec<(String, Vec<&ComObject>)> = groups.into_values().collect();
+    groups.sort_by(|(a, x), (b, y)| {
+        y.len().cmp(&x.len()).then_with(|| a.to_lowercase().cmp(&b.to_lowercase()))
+    });
+    if !unknown.is_empty() {
+        groups.push((UNKNOWN_GROUP.to_string(), unknown));
+    }
+    groups
+}
+
+/// Aggregate statistics of a result set, as printed above the listing and by `--summary`
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub total: usize,
+    pub with_prog_id: usize,
+    pub controls: usize,
+    /// Object count per `ThreadingModel`, largest first, servers without one as [`UNKNOWN_GROUP`]
+    #[serde(serialize_with = "serialize_counts")]
+    pub threading_models: Vec<(String, usize)>,
+    /// Object count per vendor (signer, else company name), largest first. Empty unless
+    /// `--check-signers` or `--check-version-info` found a vendor for at least one object.
+    #[serde(serialize_with = "serialize_counts")]
+    pub vendors: Vec<(String, usize)>,
+    /// How many of the objects each registry view registers, in scan order. Empty unless more
+    /// than one view was read; a CLSID in both views counts in each but once in `total`.
+    #[serde(serialize_with = "serialize_counts")]
+    pub views: Vec<(String, usize)>,
+}
+
+/// Write name/count pairs as a JSON object, keeping their order
+fn serialize_counts<S: Serializer>(
+    counts: &[(String, usize)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(counts.iter().map(|(name, count)| (name, count)))
+}
+
+/// The `--stats-json` document: the [`Summary`] with the scan duration and the views read
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanMetrics {
+    #[serde(flatten)]
+    pub summary: Summary,
+    pub without_prog_id: usize,
+    /// Seconds the registry scan took; 0 when the results came from `--cache`
+    pub scan_seconds: f64,
+    /// Names of the registry views that were scanned, in scan order
+    pub views_scanned: Vec<String>,
+}
+
+impl ScanMetrics {
+    pub fn new(summary: Summary, scan_seconds: f64, views_scanned: &[&str]) -> Self {
+        Self {
+            without_prog_id: summary.without_prog_id(),
+            summary,
+            scan_seconds,
+            views_scanned: views_scanned.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+}
+
+impl Summary {
+    pub fn without_prog_id(&self) -> usize {
+        self.total - self.with_prog_id
+    }
+
+    /// Share of the objects that have a ProgID, in percent (0 for no objects)
+    pub fn prog_id_percent(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.with_prog_id as f64 / self.total as f64 * 100.0
+        }
+    }
+}
+
+/// Count the objects by ProgID, control flag, threading model of the in-process server, vendor
+/// and registry view. Objects without an `InprocServer32` have no threading model and are not
+/// counted there. `views` holds the CLSIDs each view returned, before merging; objects that
+/// later filters removed are not counted.
+pub fn compute_summary(
+    objects: &HashMap<String, ComObject>,
+    views: &[(&str, HashSet<String>)],
+) -> Summary {
+    fn vendor(obj: &ComObject) -> Option<&str> {
+        obj.signer.as_deref().or(obj.company_name.as_deref())
+    }
+    let counts = |groups: Vec<(String, Vec<&ComObject>)>| {
+        groups.into_iter().map(|(name, members)| (name, members.len())).collect()
+    };
+    let in_process = objects.values().filter(|obj| obj.inproc_server.is_some());
+    let vendors = if objects.values().any(|obj| vendor(obj).is_some()) {
+        counts(group_by(objects.values(), vendor))
+    } else {
+        Vec::new()
+    };
+
+    let views = if views.len() > 1 {
+        views
+            .iter()
+            .map(|(name, clsids)| {
+                (name.to_string(), objects.keys().filter(|clsid| clsids.contains(*clsid)).count())
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Summary {
+        total: objects.len(),
+        with_prog_id: objects.values().filter(|obj| obj.prog_id.is_some()).count(),
+        controls: objects.values().filter(|obj| obj.is_control).count(),
+        threading_models: counts(group_by(in_process, |obj| obj.threading_model.as_deref())),
+        vendors,
+        views,
+    }
+}
+
+/// One field that differs between the two registry views' registrations of a CLSID
+#[derive(Debug, Clone)]
+pub struct ViewConflict {
+    pub clsid: String,
+    pub field: &'static str,
+    pub first: Option<String>,
+    pub second: Option<String>,
+}
+
+/// Compare the objects found in two registry views and return every field that disagrees
+/// for a CLSID registered in both, sorted by CLSID. Values are compared ignoring case, since
+/// ProgIDs and paths are case-insensitive on Windows.
+pub fn view_conflicts(
+    first: &HashMap<String, ComObject>,
+    second: &HashMap<String, ComObject>,
+) -> Vec<ViewConflict> {
+    let mut conflicts = Vec::new();
+    for (clsid, a) in first {
+        let Some(b) = second.get(clsid) else {
+            continue;
+        };
+
+        let fields = [
+            ("ProgID", &a.prog_id, &b.prog_id),
+            ("CurrentProgID", &a.resolved_prog_id, &b.resolved_prog_id),
+            ("InprocServer32", &a.inproc_server, &b.inproc_server),
+            ("LocalServer32", &a.local_server, &b.local_server),
+            ("AppID", &a.app_id, &b.app_id),
+        ];
+        for (field, x, y) in fields {
+            let same = match (x, y) {
+                (Some(x), Some(y)) => x.eq_ignore_ascii_case(y),
+                (None, None) => true,
+                _ => false,
+            };
+            if !same {
+                conflicts.push(ViewConflict {
+                    clsid: a.clsid.clone(),
+                    field,
+                    first: x.clone(),
+                    second: y.clone(),
+                });
+            }
+        }
+    }
+    conflicts.sort_by(|x, y| x.clsid.cmp(&y.clsid));
+    conflicts
+}
+
+/// SHA-256 digest (lowercase hex) of the complete results. Objects are hashed in CLSID order
+/// as their JSON serialization, each terminated by a newline, so the digest does not depend on
+/// `HashMap` iteration or `--sort` order and identical machine states hash identically.
+pub fn fingerprint(objects: &HashMap<String, ComObject>) -> Result<String> {
+    let mut sorted: Vec<&ComObject> = objects.values().collect();
+    sorted.sort_by(|a, b| a.clsid.cmp(&b.clsid));
+
+    let mut hasher = Sha256::new();
+    for obj in sorted {
+        hasher.update(serde_json::to_vec(obj)?);
+        hasher.update(b"\n");
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RegistryRoot;
+
+    fn objects(registrations: &[(&str, Option<&str>)]) -> HashMap<String, ComObject> {
+        registrations
+            .iter()
+            .map(|&(clsid, prog_id)| {
+                let mut obj = ComObject::new(clsid.to_string(), RegistryRoot::ClassesRoot);
+                obj.prog_id = prog_id.map(str::to_string);
+                (clsid.to_string(), obj)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn duplicate_prog_ids_groups_ignoring_case_and_sorts() {
+        let objects = objects(&[
+            ("{00000000-0000-0000-0000-000000000004}", Some("Zeta.App")),
+            ("{00000000-0000-0000-0000-000000000003}", Some("zeta.app")),
+            ("{00000000-0000-0000-0000-000000000002}", Some("Alpha.App")),
+            ("{00000000-0000-0000-0000-000000000001}", Some("ALPHA.APP")),
+            ("{00000000-0000-0000-0000-000000000005}", Some("Alpha.App")),
+            ("{00000000-0000-0000-0000-000000000006}", Some("Single.App")),
+            ("{00000000-0000-0000-0000-000000000007}", None),
+        ]);
+        let duplicates = duplicate_prog_ids(&objects);
+
+        let prog_ids: Vec<String> =
+            duplicates.iter().map(|(prog_id, _)| prog_id.to_lowercase()).collect();
+        assert_eq!(prog_ids, ["alpha.app", "zeta.app"]);
+        assert_eq!(
+            duplicates[0].1,
+            [
+                "{00000000-0000-0000-0000-000000000001}",
+                "{00000000-0000-0000-0000-000000000002}",
+                "{00000000-0000-0000-0000-000000000005}",
+            ]
+        );
+        assert_eq!(
+            duplicates[1].1,
+            ["{00000000-0000-0000-0000-000000000003}", "{00000000-0000-0000-0000-000000000004}"]
+        );
+    }
+
+    #[test]
+    fn duplicate_prog_ids_is_empty_without_collisions() {
+        let objects = objects(&[
+            ("{00000000-0000-0000-0000-000000000001}", Some("One.App")),
+            ("{00000000-0000-0000-0000-000000000002}", Some("Two.App")),
+        ]);
+        assert!(duplicate_prog_ids(&objects).is_empty());
+    }
+}
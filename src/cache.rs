@@ -0,0 +1,75 @@
+//! On-disk cache of complete scans for `--cache`/`--use-cache`.
+//!
+//! A cache file stores the unfiltered objects of a scan together with the root and views it
+//! was taken from, so later runs can apply different filters without re-reading the registry.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::types::{ComObject, RegistryRoot, ScanStats};
+
+/// Contents of a cache file
+#[derive(Serialize, Deserialize)]
+pub struct ScanCache {
+    /// Unix time (seconds) the scan completed
+    pub created: u64,
+    pub root: RegistryRoot,
+    /// Names of the registry views that were scanned, e.g. `["32-bit", "64-bit"]`
+    pub views: Vec<String>,
+    pub stats: ScanStats,
+    pub objects: Vec<ComObject>,
+}
+
+/// Write a completed, unfiltered scan to the cache file
+pub fn save(
+    path: &str,
+    root: RegistryRoot,
+    views: &[&str],
+    stats: &ScanStats,
+    objects: impl IntoIterator<Item = ComObject>,
+) -> Result<()> {
+    let cache = ScanCache {
+        created: now(),
+        root,
+        views: views.iter().map(|view| view.to_string()).collect(),
+        stats: *stats,
+        objects: objects.into_iter().collect(),
+    };
+
+    let file = File::create(path).with_context(|| format!("Failed to create {path}"))?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer(&mut writer, &cache)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Load the cache file, failing if it is older than `max_age` seconds or was taken from a
+/// different root or set of views than the ones requested now
+pub fn load(path: &str, root: RegistryRoot, views: &[&str], max_age: u64) -> Result<ScanCache> {
+    let file = File::open(path).with_context(|| format!("Failed to open {path}"))?;
+    let cache: ScanCache = serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("Failed to parse {path}"))?;
+
+    let age = now().saturating_sub(cache.created);
+    if age > max_age {
+        bail!("the cache is {age}s old (--cache-max-age is {max_age}s)");
+    }
+    if cache.root != root {
+        bail!("the cache was taken from a different registry root");
+    }
+    if cache.views.iter().map(String::as_str).ne(views.iter().copied()) {
+        bail!("the cache covers the {} view(s)", cache.views.join(" and "));
+    }
+    Ok(cache)
+}
+
+/// Current Unix time in seconds
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,52 @@
+//! Well-known COM component categories.
+//!
+//! This module keeps every category identifier (CATID) the inspector cares about in one place,
+//! so the list of security-relevant GUIDs is easy to audit.
+
+use crate::types::ComponentCategory;
+
+/// The object is safe to script from untrusted code (e.g. a web page)
+pub const CATID_SAFE_FOR_SCRIPTING: &str = "{7DD95801-9882-11CF-9FA9-00AA006C42C4}";
+
+/// The object is safe to initialize from untrusted persistent data
+pub const CATID_SAFE_FOR_INITIALIZING: &str = "{7DD95802-9882-11CF-9FA9-00AA006C42C4}";
+
+/// Well-known category GUIDs and their names, used before falling back to the
+/// `HKEY_CLASSES_ROOT\Component Categories` descriptions
+pub const WELL_KNOWN_CATEGORIES: &[(&str, &str)] = &[
+    (CATID_SAFE_FOR_SCRIPTING, "Safe for Scripting"),
+    (CATID_SAFE_FOR_INITIALIZING, "Safe for Initializing"),
+    ("{40FC6ED3-2438-11CF-A3DB-080036F12502}", "Insertable"),
+    ("{40FC6ED4-2438-11CF-A3DB-080036F12502}", "Control"),
+    ("{40FC6ED5-2438-11CF-A3DB-080036F12502}", "Programmable"),
+    ("{40FC6ED6-2438-11CF-A3DB-080036F12502}", "Is Shortcut"),
+    ("{40FC6ED7-2438-11CF-A3DB-080036F12502}", "Never Show Extension"),
+    ("{40FC6ED8-2438-11CF-A3DB-080036F12502}", "Document Object"),
+    ("{40FC6ED9-2438-11CF-A3DB-080036F12502}", "Printable"),
+    ("{0DE86A50-2BAA-11CF-A229-00AA003D7352}", "Requires Data Path Host"),
+    ("{0DE86A51-2BAA-11CF-A229-00AA003D7352}", "Persists to Moniker"),
+    ("{0DE86A52-2BAA-11CF-A229-00AA003D7352}", "Persists to Storage"),
+    ("{0DE86A53-2BAA-11CF-A229-00AA003D7352}", "Persists to Stream (Init)"),
+    ("{0DE86A54-2BAA-11CF-A229-00AA003D7352}", "Persists to Stream"),
+    ("{0DE86A55-2BAA-11CF-A229-00AA003D7352}", "Persists to Memory"),
+    ("{0DE86A56-2BAA-11CF-A229-00AA003D7352}", "Persists to File"),
+    ("{0DE86A57-2BAA-11CF-A229-00AA003D7352}", "Persists to Property Bag"),
+    ("{0DE86A58-2BAA-11CF-A229-00AA003D7352}", "Internet Aware"),
+];
+
+/// Looks up the name of a well-known category GUID (case-insensitive)
+pub fn well_known_category_name(catid: &str) -> Option<&'static str> {
+    WELL_KNOWN_CATEGORIES
+        .iter()
+        .find(|(guid, _)| guid.eq_ignore_ascii_case(catid))
+        .map(|(_, name)| *name)
+}
+
+/// Returns true if any of the implemented categories marks the object as safe for scripting
+/// or safe for initializing
+pub fn is_safe_for_scripting(categories: &[ComponentCategory]) -> bool {
+    categories.iter().any(|category| {
+        category.catid.eq_ignore_ascii_case(CATID_SAFE_FOR_SCRIPTING)
+            || category.catid.eq_ignore_ascii_case(CATID_SAFE_FOR_INITIALIZING)
+    })
+}
@@ -0,0 +1,104 @@
+//! Effective class registrations for `--resolve-classes`.
+//!
+//! `HKEY_CLASSES_ROOT` is not a tree of its own but a view that merges
+//! `HKEY_CURRENT_USER\Software\Classes` over `HKEY_LOCAL_MACHINE\SOFTWARE\Classes`. Reading it
+//! through the WOW64 view flags mixes that merge with the 32/64-bit redirection, so this module
+//! reads both trees directly and rebuilds the view a COM client in the given bitness sees:
+//!
+//! 1. Each tree is read on its own. On 64-bit Windows the 32-bit view of a `Classes` tree is
+//!    its `WOW6432Node\CLSID` key and the 64-bit view its `CLSID` key; the view flag selects
+//!    one of them on the real `Classes` key, never on the merged HKCR.
+//! 2. A CLSID registered in only one tree is taken from that tree.
+//! 3. A CLSID registered in both is resolved key by key, as HKCR does: a value that the
+//!    per-user key sets wins, and the machine-wide key fills in what the per-user key lacks.
+//!    Subkey lists such as `Implemented Categories` are the union of both.
+//! 4. The filters are applied to the resolved object, so a filter that only the machine-wide
+//!    half matches still finds a class the user partly overrides.
+//!
+//! A resolved object is `shadowed` when its per-user key points the class at a different
+//! `InprocServer32` or `LocalServer32` than the machine-wide key, which is how most per-user
+//! COM hijacks redirect a system class to their own binary.
+
+use anyhow::Result;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use windows::Win32::System::Registry::REG_SAM_FLAGS;
+
+use crate::filter::{should_include_object, FilterCriteria};
+use crate::registry::{scan_com_objects_with_progress, ScanOptions};
+use crate::types::{ComObject, RegistrationScope, RegistryRoot, ScanStats};
+
+/// Reads the per-user and machine-wide `CLSID` trees of one registry view and resolves them
+/// into the objects HKCR presents, keeping those that pass the filters.
+/// `on_progress` receives `(processed, matched)` across both trees; matches are only known
+/// once both are read. Stops early, keeping what was collected, once `cancel` is set.
+/// Each tree is read with the workers and deadline of `options`; its limit is ignored.
+pub fn scan_resolved(
+    view_flag: REG_SAM_FLAGS,
+    options: ScanOptions,
+    criteria: &FilterCriteria,
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(usize, usize),
+) -> Result<(HashMap<String, ComObject>, ScanStats)> {
+    let everything = FilterCriteria::default();
+    let options = ScanOptions { limit: 0, ..options };
+
+    // Most users have no per-user classes in the 32-bit view, so a missing tree is empty
+    let (user_objects, user_stats) = scan_com_objects_with_progress(
+        RegistryRoot::CurrentUser,
+        view_flag,
+        options,
+        &everything,
+        cancel,
+        |processed, _| on_progress(processed, 0),
+    )
+    .unwrap_or_default();
+
+    let (machine_objects, machine_stats) = scan_com_objects_with_progress(
+        RegistryRoot::LocalMachine,
+        view_flag,
+        options,
+        &everything,
+        cancel,
+        |processed, _| on_progress(user_stats.enumerated + processed, 0),
+    )?;
+
+    // Which tree an object came from is its scope, so it needs no extra lookups
+    let mut objects = user_objects;
+    for obj in objects.values_mut() {
+        obj.scope = Some(RegistrationScope::User);
+    }
+    for (clsid, mut machine) in machine_objects {
+        machine.scope = Some(RegistrationScope::Machine);
+        match objects.entry(clsid) {
+            Entry::Occupied(mut entry) => {
+                let user = entry.get_mut();
+                let shadowed = shadows(user, &machine);
+                user.merge(machine);
+                user.shadowed = shadowed;
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(machine);
+            }
+        }
+    }
+    objects.retain(|_, obj| should_include_object(obj, criteria));
+
+    let mut stats = user_stats;
+    stats.add(&machine_stats);
+    stats.matched = objects.len();
+    on_progress(stats.enumerated, stats.matched);
+    Ok((objects, stats))
+}
+
+/// True if the per-user registration sets a server that differs from the machine-wide one
+fn shadows(user: &ComObject, machine: &ComObject) -> bool {
+    let differs = |user: &Option<String>, machine: &Option<String>| {
+        user.as_deref().is_some_and(|server| {
+            machine.as_deref().is_none_or(|other| !server.eq_ignore_ascii_case(other))
+        })
+    };
+    differs(&user.inproc_server, &machine.inproc_server)
+        || differs(&user.local_server, &machine.local_server)
+}
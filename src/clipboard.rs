@@ -0,0 +1,46 @@
+//! Windows clipboard access.
+//!
+//! This module places exported text on the clipboard as `CF_UNICODETEXT`.
+
+use anyhow::Result;
+use windows::Win32::Foundation::{GlobalFree, HANDLE, HWND};
+use windows::Win32::System::DataExchange::{
+    CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData,
+};
+use windows::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+
+/// Standard clipboard format for UTF-16 text
+const CF_UNICODETEXT: u32 = 13;
+
+/// Replace the clipboard contents with the given text
+pub fn set_text(text: &str) -> Result<()> {
+    let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+
+    unsafe {
+        OpenClipboard(HWND::default())?;
+        let result = write_clipboard(&wide);
+        let _ = CloseClipboard();
+        result
+    }
+}
+
+/// Copy the null-terminated UTF-16 text into a movable global block and hand it to the
+/// clipboard. The clipboard must be open; on success it owns the memory.
+unsafe fn write_clipboard(wide: &[u16]) -> Result<()> {
+    EmptyClipboard()?;
+
+    let hglobal = GlobalAlloc(GMEM_MOVEABLE, std::mem::size_of_val(wide))?;
+    let target = GlobalLock(hglobal) as *mut u16;
+    if target.is_null() {
+        let _ = GlobalFree(hglobal);
+        return Err(anyhow::anyhow!("Failed to lock clipboard memory"));
+    }
+    std::ptr::copy_nonoverlapping(wide.as_ptr(), target, wide.len());
+    let _ = GlobalUnlock(hglobal);
+
+    if let Err(e) = SetClipboardData(CF_UNICODETEXT, HANDLE(hglobal.0 as isize)) {
+        let _ = GlobalFree(hglobal);
+        return Err(e.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,255 @@
+//! Comparison of a scan against a baseline export.
+//!
+//! A baseline is a JSON export (`--format json`) of an approved machine state. Objects are
+//! matched by CLSID, ignoring case and braces. Objects in both are compared field by field,
+//! ignoring case, for the [`COMPARED_FIELDS`] the baseline record contains, so a compact
+//! baseline made with `--columns CLSID,ProgID` only checks the ProgID.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::BufReader;
+
+use crate::filter::normalize_clsid;
+use crate::types::ComObject;
+
+/// A field compared between a baseline record and the current object
+pub struct ComparedField {
+    /// Column name shown in the report
+    pub name: &'static str,
+    /// Key of the field in a JSON export
+    pub key: &'static str,
+    value: fn(&ComObject) -> Option<&str>,
+}
+
+/// Every field a baseline can pin
+pub const COMPARED_FIELDS: &[ComparedField] = &[
+    ComparedField {
+        name: "ProgID",
+        key: "prog_id",
+        value: |obj| obj.prog_id.as_deref(),
+    },
+    ComparedField {
+        name: "Description",
+        key: "description",
+        value: |obj| obj.description.as_deref(),
+    },
+    ComparedField {
+        name: "InprocServer32",
+        key: "inproc_server",
+        value: |obj| obj.inproc_server.as_deref(),
+    },
+    ComparedField {
+        name: "LocalServer32",
+        key: "local_server",
+        value: |obj| obj.local_server.as_deref(),
+    },
+    ComparedField {
+        name: "AppID",
+        key: "app_id",
+        value: |obj| obj.app_id.as_deref(),
+    },
+];
+
+/// How an object differs from the baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DeviationStatus {
+    /// Present now but absent from the baseline
+    Unexpected,
+    /// Listed in the baseline but missing now
+    Removed,
+    /// Present in both, with at least one compared field that differs
+    Changed,
+}
+
+impl DeviationStatus {
+    /// Report label of the status
+    pub fn label(self) -> &'static str {
+        match self {
+            DeviationStatus::Unexpected => "UNEXPECTED",
+            DeviationStatus::Removed => "REMOVED",
+            DeviationStatus::Changed => "CHANGED",
+        }
+    }
+}
+
+/// One object that deviates from the baseline
+#[derive(Debug, Clone)]
+pub struct Deviation {
+    pub status: DeviationStatus,
+    pub clsid: String,
+    pub prog_id: Option<String>,
+    /// The fields that differ, for `Changed`; empty otherwise
+    pub changes: Vec<FieldChange>,
+}
+
+/// A compared field whose baseline value differs from the current one
+#[derive(Debug, Clone)]
+pub struct FieldChange {
+    /// Column name of the [`ComparedField`]
+    pub field: &'static str,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// One approved object of a baseline
+#[derive(Debug, Clone)]
+pub struct BaselineEntry {
+    /// The CLSID as written in the file
+    pub clsid: String,
+    pub prog_id: Option<String>,
+    /// Value of every [`COMPARED_FIELDS`] key the record has, by JSON key (`None` for `null`)
+    pub fields: HashMap<&'static str, Option<String>>,
+}
+
+/// A loaded baseline: every approved object
+pub struct Baseline {
+    pub entries: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    /// Load a JSON export. Every record must have a `clsid` key; the other fields are
+    /// optional, so exports made with `--columns CLSID,ProgID` work as compact baselines.
+    pub fn load(path: &str) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("Failed to open {path}"))?;
+        let records: Vec<Value> = serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("Failed to parse {path} as a JSON export"))?;
+
+        let entries = records
+            .iter()
+            .enumerate()
+            .map(|(index, record)| {
+                let clsid = record["clsid"]
+                    .as_str()
+                    .with_context(|| format!("Record {index} of {path} has no \"clsid\""))?;
+                let fields = COMPARED_FIELDS
+                    .iter()
+                    .filter_map(|field| {
+                        let value = record.get(field.key)?;
+                        Some((field.key, value.as_str().map(str::to_string)))
+                    })
+                    .collect();
+                Ok(BaselineEntry {
+                    clsid: clsid.to_string(),
+                    prog_id: record["prog_id"].as_str().map(str::to_string),
+                    fields,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { entries })
+    }
+}
+
+/// List the objects found now but not in the baseline, the baseline objects that are gone, and
+/// the objects whose compared fields changed, sorted by status and then CLSID
+pub fn compare_to_baseline(
+    objects: &HashMap<String, ComObject>,
+    baseline: &Baseline,
+) -> Vec<Deviation> {
+    let current: HashMap<String, &ComObject> =
+        objects.values().map(|obj| (normalize_clsid(&obj.clsid), obj)).collect();
+    let approved: HashSet<String> =
+        baseline.entries.iter().map(|entry| normalize_clsid(&entry.clsid)).collect();
+
+    let mut deviations: Vec<Deviation> = objects
+        .values()
+        .filter(|obj| !approved.contains(&normalize_clsid(&obj.clsid)))
+        .map(|obj| Deviation {
+            status: DeviationStatus::Unexpected,
+            clsid: obj.clsid.clone(),
+            prog_id: obj.prog_id.clone(),
+            changes: Vec::new(),
+        })
+        .collect();
+    for entry in &baseline.entries {
+        match current.get(&normalize_clsid(&entry.clsid)) {
+            None => deviations.push(Deviation {
+                status: DeviationStatus::Removed,
+                clsid: entry.clsid.clone(),
+                prog_id: entry.prog_id.clone(),
+                changes: Vec::new(),
+            }),
+            Some(obj) => {
+                let changes = field_changes(entry, obj);
+                if !changes.is_empty() {
+                    deviations.push(Deviation {
+                        status: DeviationStatus::Changed,
+                        clsid: obj.clsid.clone(),
+                        prog_id: obj.prog_id.clone(),
+                        changes,
+                    });
+                }
+            }
+        }
+    }
+    deviations.sort_by_key(|d| (d.status, normalize_clsid(&d.clsid)));
+    deviations
+}
+
+/// The compared fields of the baseline record that differ from the object. Like
+/// [`view_conflicts`](crate::analysis::view_conflicts), values that differ only in case are
+/// equal, since Windows looks up paths, ProgIDs and GUIDs case-insensitively.
+fn field_changes(entry: &BaselineEntry, obj: &ComObject) -> Vec<FieldChange> {
+    COMPARED_FIELDS
+        .iter()
+        .filter_map(|field| {
+            let old = entry.fields.get(field.key)?;
+            let new = (field.value)(obj);
+            let same = match (old.as_deref(), new) {
+                (Some(old), Some(new)) => old.eq_ignore_ascii_case(new),
+                (old, new) => old == new,
+            };
+            (!same).then(|| FieldChange {
+                field: field.name,
+                old: old.clone(),
+                new: new.map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RegistryRoot;
+
+    const CLSID: &str = "{00000000-0000-0000-0000-000000000001}";
+
+    fn objects(inproc_server: &str) -> HashMap<String, ComObject> {
+        let mut obj = ComObject::new(CLSID.to_string(), RegistryRoot::ClassesRoot);
+        obj.inproc_server = Some(inproc_server.to_string());
+        HashMap::from([(CLSID.to_string(), obj)])
+    }
+
+    fn baseline(inproc_server: &str) -> Baseline {
+        let fields = HashMap::from([("inproc_server", Some(inproc_server.to_string()))]);
+        Baseline {
+            entries: vec![BaselineEntry {
+                clsid: CLSID.to_lowercase().trim_matches(['{', '}']).to_string(),
+                prog_id: None,
+                fields,
+            }],
+        }
+    }
+
+    #[test]
+    fn case_only_changes_are_not_deviations() {
+        let current = objects(r"C:\Windows\System32\Example.dll");
+        let approved = baseline(r"c:\windows\system32\example.dll");
+        let deviations = compare_to_baseline(&current, &approved);
+        assert!(deviations.is_empty());
+    }
+
+    #[test]
+    fn changed_values_are_reported() {
+        let current = objects(r"C:\Temp\Example.dll");
+        let deviations = compare_to_baseline(&current, &baseline(r"C:\Windows\Example.dll"));
+        assert_eq!(deviations.len(), 1);
+        assert_eq!(deviations[0].status, DeviationStatus::Changed);
+        let change = &deviations[0].changes[0];
+        assert_eq!(change.field, "InprocServer32");
+        assert_eq!(change.old.as_deref(), Some(r"C:\Windows\Example.dll"));
+        assert_eq!(change.new.as_deref(), Some(r"C:\Temp\Example.dll"));
+    }
+}
@@ -23,6 +23,60 @@ pub fn init_console_utf8() -> bool {
     }
 }
 
+/// Let the console interpret ANSI escape sequences, for colored reports (`--color`).
+/// Returns true on success.
+pub fn enable_ansi_colors() -> bool {
+    #[cfg(windows)]
+    {
+        use windows::Win32::System::Console::{
+            GetConsoleMode, GetStdHandle, SetConsoleMode, CONSOLE_MODE,
+            ENABLE_VIRTUAL_TERMINAL_PROCESSING, STD_OUTPUT_HANDLE,
+        };
+
+        unsafe {
+            let Ok(handle) = GetStdHandle(STD_OUTPUT_HANDLE) else {
+                return false;
+            };
+            let mut mode = CONSOLE_MODE::default();
+            if GetConsoleMode(handle, &mut mode).is_err() {
+                return false;
+            }
+            SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING).is_ok()
+        }
+    }
+
+    #[cfg(not(windows))]
+    {
+        // Other terminals interpret ANSI escape sequences natively
+        true
+    }
+}
+
+/// Width of the console window in columns, or `None` when stdout is not a console (for
+/// example when it is redirected to a file)
+pub fn terminal_width() -> Option<usize> {
+    #[cfg(windows)]
+    {
+        use windows::Win32::System::Console::{
+            GetConsoleScreenBufferInfo, GetStdHandle, CONSOLE_SCREEN_BUFFER_INFO,
+            STD_OUTPUT_HANDLE,
+        };
+
+        unsafe {
+            let handle = GetStdHandle(STD_OUTPUT_HANDLE).ok()?;
+            let mut info = CONSOLE_SCREEN_BUFFER_INFO::default();
+            GetConsoleScreenBufferInfo(handle, &mut info).ok()?;
+            let width = info.srWindow.Right - info.srWindow.Left + 1;
+            usize::try_from(width).ok().filter(|&width| width > 0)
+        }
+    }
+
+    #[cfg(not(windows))]
+    {
+        None
+    }
+}
+
 /// Print the original Unicode header art (uses box-drawing and other glyphs).
 pub fn print_header_art_unicode() {
     let art = r#"
@@ -1,18 +1,225 @@
 //! Display and export functionality for COM object results.
 //! 
-//! This module handles result presentation to the user and exporting to various formats (txt, csv).
+//! This module handles result presentation to the user and exporting to various formats (txt, csv, tsv, json, jsonl, html, siem, xlsx).
 
 use anyhow::Result;
-use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::Write;
-use csv::Writer;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use csv::{Writer, WriterBuilder};
+use rust_xlsxwriter::{Format, Workbook};
 
-use crate::types::ComObject;
+use crate::analysis::{
+    compute_summary, duplicate_prog_ids, group_by, multi_prog_ids, view_conflicts, ScanMetrics,
+    Summary,
+};
+use crate::clipboard;
+use crate::console::terminal_width;
+use crate::compare::{Baseline, Deviation, DeviationStatus};
+use crate::interrupt;
+use crate::security_scan::suspicion_factors;
+use crate::server_path::server_binary;
+use crate::shell;
+use crate::types::{
+    value_type_name, Args, ComObject, KeyDump, Record, ResultSet, ScanStats, SortKey,
+    TreatAsTarget, TypeLibVersion, UsabilityLevel, VendorSource,
+};
+use crate::usability::{DefaultScorer, UsabilityScorer};
 
-/// Display results to console with optional verbose output
-pub fn display_results(objects: &HashMap<String, ComObject>, verbose: bool) -> Result<()> {
+/// SGR color codes of the `--baseline` report
+const ANSI_GREEN: &str = "32";
+const ANSI_RED: &str = "31";
+const ANSI_YELLOW: &str = "33";
+
+/// The UTF-8 byte order mark, written ahead of TXT/CSV/TSV exports when `--bom` is set
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Export formats offered by the export prompt and accepted by `--format`
+pub const EXPORT_FORMATS: &[&str] =
+    &["txt", "csv", "tsv", "json", "jsonl", "html", "siem", "xlsx"];
+
+/// The export format named by a file's extension (case-insensitive), for `--output`
+/// without `--format`
+pub fn format_for_path(path: &str) -> Option<&'static str> {
+    let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+    let extension = if extension == "htm" { "html" } else { extension.as_str() };
+    EXPORT_FORMATS.iter().copied().find(|format| *format == extension)
+}
+
+/// Export formats available for the records of a focused scan ([`ResultSet`])
+pub const RECORD_EXPORT_FORMATS: &[&str] = &["txt", "csv", "tsv", "json", "jsonl"];
+
+/// Column header of CSV/TSV exports. New columns are only ever appended, so downstream
+/// parsers that index columns by position keep working.
+const CSV_COLUMNS: [&str; 24] = [
+    "CLSID",
+    "ProgID",
+    "Description",
+    "Usability",
+    "CurrentProgID",
+    "InprocServer32",
+    "LocalServer32",
+    "AppID",
+    "LastWriteTime",
+    "WritableByUsers",
+    "BinaryHijackable",
+    "UnquotedPath",
+    "SuspicionScore",
+    "CurrentlyLoaded",
+    "Signer",
+    "CompanyName",
+    "ProductName",
+    "Shadowed",
+    "RunAs",
+    "Instantiable",
+    "ThreadingModel",
+    "TypeLib",
+    "TreatAs",
+    "TreatAsTarget",
+];
+
+/// Column names accepted by `--columns`, with the JSON key each one selects.
+/// `Usability` is computed and only appears in JSON when selected explicitly.
+const EXPORT_COLUMNS: &[(&str, &str)] = &[
+    ("CLSID", "clsid"),
+    ("ProgID", "prog_id"),
+    ("Description", "description"),
+    ("Usability", "usability"),
+    ("CurrentProgID", "resolved_prog_id"),
+    ("InprocServer32", "inproc_server"),
+    ("ThreadingModel", "threading_model"),
+    ("LocalServer32", "local_server"),
+    ("AppID", "app_id"),
+    ("RunAs", "run_as"),
+    ("TypeLib", "type_lib"),
+    ("TreatAs", "treat_as"),
+    ("TreatAsTarget", "treat_as_target"),
+    ("Root", "root"),
+    ("Scope", "scope"),
+    ("ImplementedCategories", "implemented_categories"),
+    ("SafeForScripting", "safe_for_scripting"),
+    ("Control", "is_control"),
+    ("Insertable", "is_insertable"),
+    ("LastWriteTime", "last_write_time"),
+    ("WritableByUsers", "writable_by_users"),
+    ("BinaryHijackable", "binary_hijackable"),
+    ("UnquotedPath", "unquoted_path"),
+    ("SuspicionScore", "suspicion_score"),
+    ("CurrentlyLoaded", "currently_loaded"),
+    ("Instantiable", "instantiable"),
+    ("Signer", "signer"),
+    ("CompanyName", "company_name"),
+    ("ProductName", "product_name"),
+    ("ShellHandlers", "shell_handlers"),
+    ("ShellEx", "shellex"),
+    ("Shadowed", "shadowed"),
+];
+
+/// Prefix of the export column that holds an `--extra-value` value, e.g. `Extra:FriendlyName`
+const EXTRA_COLUMN_PREFIX: &str = "Extra:";
+
+/// Validates a `--columns` entry (column name or JSON key, case-insensitive)
+/// and returns the canonical column name
+pub fn parse_column(name: &str) -> Result<String, String> {
+    EXPORT_COLUMNS
+        .iter()
+        .find(|(column, key)| column.eq_ignore_ascii_case(name) || key.eq_ignore_ascii_case(name))
+        .map(|(column, _)| column.to_string())
+        .ok_or_else(|| {
+            let names: Vec<&str> = EXPORT_COLUMNS.iter().map(|(column, _)| *column).collect();
+            format!("unknown column (valid columns: {})", names.join(", "))
+        })
+}
+
+/// Output options shared by the console listing and all exporters
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// Prepend a UTF-8 BOM to TXT, CSV and TSV exports
+    pub bom: bool,
+    /// Write JSON exports on a single line instead of pretty-printed
+    pub json_compact: bool,
+    /// Order of objects in the listing and in every export
+    pub sort: SortOptions,
+    /// Columns of CSV/TSV/HTML exports and keys of JSON objects; `None` means all
+    pub columns: Option<Vec<String>>,
+    /// One `Extra:<name>` column per `--extra-value`, appended to the tabular exports
+    pub extra_columns: Vec<String>,
+    /// Format used by `--clipboard` and `--output`
+    pub format: String,
+    /// Open the exported file with its default application after a successful export
+    pub open: bool,
+    /// Rates objects for the usability column, listing and sort order
+    pub usability: Arc<dyn UsabilityScorer>,
+    /// Print only the statistics of the results, without listing the objects
+    pub summary: bool,
+    /// Color console reports with ANSI escape sequences (`--color`)
+    pub color: bool,
+    /// Show the registry type of string values in the detailed listing (`--show-value-types`)
+    pub show_value_types: bool,
+    /// Print and write TXT/CSV/TSV values with their control characters untouched
+    /// (`--raw-values`)
+    pub raw_values: bool,
+}
+
+/// Key and direction used to order results
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SortOptions {
+    pub key: SortKey,
+    /// Reverse the order of the chosen key
+    pub descending: bool,
+}
+
+impl ExportOptions {
+    /// Build the export options from the command-line arguments
+    pub fn from_args(args: &Args) -> Self {
+        Self {
+            bom: args.bom,
+            json_compact: args.json_compact,
+            // `--suspicious` always lists the most suspicious objects first
+            sort: SortOptions {
+                key: if args.suspicious { SortKey::Suspicion } else { args.sort },
+                descending: args.sort_desc && !args.suspicious,
+            },
+            columns: args.columns.clone(),
+            extra_columns: args
+                .extra_value
+                .iter()
+                .map(|name| format!("{EXTRA_COLUMN_PREFIX}{name}"))
+                .collect(),
+            format: args
+                .format
+                .clone()
+                .or_else(|| args.output.as_deref().and_then(format_for_path).map(str::to_string))
+                .unwrap_or_else(|| "txt".to_string()),
+            open: args.open,
+            usability: Arc::new(DefaultScorer),
+            summary: args.summary,
+            color: args.color.enabled(),
+            show_value_types: args.show_value_types,
+            raw_values: args.raw_values,
+        }
+    }
+}
+
+/// Display results to console. `verbose` is the `-v` count: 0 lists ProgIDs only, 1 adds the
+/// detailed listing, 2 adds server paths and categories, 3 shows every captured field.
+/// `views` holds the CLSIDs each registry view returned, for the per-view counts.
+/// With `--summary` only the statistics are printed.
+pub fn display_results(
+    objects: &HashMap<String, ComObject>,
+    stats: &ScanStats,
+    views: &[(&str, HashSet<String>)],
+    verbose: u8,
+    options: &ExportOptions,
+) -> Result<()> {
     println!("=== Results ===");
+    println!("Scanned {} CLSIDs, matched {}", stats.enumerated, stats.matched);
     println!("Total unique COM objects found: {}\n", objects.len());
 
     if objects.is_empty() {
@@ -20,199 +227,1743 @@ pub fn display_results(objects: &HashMap<String, ComObject>, verbose: bool) -> R
         return Ok(());
     }
 
-    // Sort by ProgID for better readability
-    let mut sorted_objects: Vec<_> = objects.values().collect();
-    sorted_objects.sort_by(|a, b| {
-        match (&a.prog_id, &b.prog_id) {
-            (Some(pa), Some(pb)) => pa.cmp(pb),
-            (Some(_), None) => std::cmp::Ordering::Less,
-            (None, Some(_)) => std::cmp::Ordering::Greater,
-            (None, None) => a.clsid.cmp(&b.clsid),
+    print_summary(&compute_summary(objects, views), options.raw_values);
+    if options.summary {
+        return Ok(());
+    }
+
+    let sorted_objects = sort_objects(objects, options);
+    if verbose > 0 {
+        println!("--- Detailed Listing ---\n");
+        for obj in sorted_objects {
+            print_object_details(obj, verbose, options);
+            println!();
         }
-    });
+    } else {
+        // Compact format: one table row per object
+        println!("--- COM Objects ---");
+        print_compact_table(&sorted_objects, options);
+    }
 
-    // Count objects with ProgIDs
-    let with_progid = sorted_objects
-        .iter()
-        .filter(|obj| obj.prog_id.is_some())
-        .count();
+    Ok(())
+}
 
+/// Print the statistics block of the results
+fn print_summary(summary: &Summary, raw: bool) {
     println!(
         "COM objects with ProgID: {} ({:.1}%)",
-        with_progid,
-        (with_progid as f64 / objects.len() as f64) * 100.0
+        summary.with_prog_id,
+        summary.prog_id_percent()
     );
-    println!("COM objects without ProgID: {}\n", objects.len() - with_progid);
+    println!("COM objects without ProgID: {}", summary.without_prog_id());
+    println!("ActiveX controls: {}\n", summary.controls);
 
-    if verbose {
-        println!("--- Detailed Listing ---\n");
-        for obj in sorted_objects {
-            println!("CLSID: {}", obj.clsid);
-            if let Some(ref prog_id) = obj.prog_id {
-                println!("  ProgID: {prog_id}");
+    if !summary.views.is_empty() {
+        let counts: Vec<String> =
+            summary.views.iter().map(|(view, count)| format!("{view}: {count}")).collect();
+        println!("Objects per view: {}, merged unique: {}\n", counts.join(", "), summary.total);
+    }
+
+    if !summary.threading_models.is_empty() {
+        println!("In-process servers by threading model:");
+        for (model, count) in &summary.threading_models {
+            println!("  {}: {count}", escape_controls(model, raw));
+        }
+        println!();
+    }
+    if !summary.vendors.is_empty() {
+        println!("Objects by vendor:");
+        for (vendor, count) in &summary.vendors {
+            println!("  {}: {count}", escape_controls(vendor, raw));
+        }
+        println!();
+    }
+}
+
+/// Print the `--follow-treatas` target of the detailed listing, below its `TreatAs` line
+fn print_treat_as_target(target: &TreatAsTarget, raw: bool) {
+    let chain = match (target.hops, target.cycle) {
+        (_, true) => " (chain cut at a cycle)".to_string(),
+        (1, false) => String::new(),
+        (hops, false) => format!(" (after {hops} redirections)"),
+    };
+    println!("  TreatAs Target: {}{chain}", escape_controls(&target.clsid, raw));
+    let fields = [
+        ("ProgID", &target.prog_id),
+        ("Description", &target.description),
+        ("InprocServer32", &target.inproc_server),
+        ("LocalServer32", &target.local_server),
+    ];
+    for (label, value) in fields {
+        if let Some(value) = value {
+            println!("    {label}: {}", escape_controls(value, raw));
+        }
+    }
+}
+
+/// Print an object on one line as its display name and CLSID, or just the CLSID if it has
+/// no friendlier name
+fn print_compact_line(obj: &ComObject, indent: &str, raw: bool) {
+    let name = escape_controls(obj.display_name(), raw);
+    let clsid = escape_controls(&obj.clsid, raw);
+    if obj.display_name() == obj.clsid {
+        println!("{indent}{clsid}");
+    } else {
+        println!("{indent}{name} ({clsid})");
+    }
+}
+
+/// Width of the CLSID column: a GUID with braces
+const CLSID_WIDTH: usize = 38;
+/// Narrowest the name column gets, however narrow the console is
+const MIN_NAME_WIDTH: usize = 12;
+/// Console width assumed when stdout is not a console
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+
+/// Print the compact listing as a table of ProgID, CLSID and usability, aligned in columns and
+/// fitted to the console width. Objects without a ProgID show their description or CLSID
+/// instead; names that do not fit are cut with an ellipsis.
+fn print_compact_table(objects: &[&ComObject], options: &ExportOptions) {
+    let raw = options.raw_values;
+    let rows: Vec<(Cow<str>, Cow<str>, &str)> = objects
+        .iter()
+        .map(|obj| {
+            let name = obj.prog_id.as_deref().unwrap_or_else(|| obj.display_name());
+            let usability = check_usability(obj, &*options.usability);
+            (escape_controls(name, raw), escape_controls(&obj.clsid, raw), usability)
+        })
+        .collect();
+    fn widest<'a>(header: &str, cells: impl Iterator<Item = &'a str>) -> usize {
+        cells.map(|cell| cell.chars().count()).fold(header.chars().count(), usize::max)
+    }
+    let usability_width = widest("Usability", rows.iter().map(|row| row.2));
+    // Two spaces of indent and two between each pair of columns
+    let fixed = 2 + CLSID_WIDTH + usability_width + 4;
+    let width = terminal_width().unwrap_or(DEFAULT_TERMINAL_WIDTH);
+    let name_width = widest("ProgID", rows.iter().map(|row| &*row.0))
+        .min(width.saturating_sub(fixed).max(MIN_NAME_WIDTH));
+
+    println!("  {:<name_width$}  {:<CLSID_WIDTH$}  Usability", "ProgID", "CLSID");
+    println!(
+        "  {}  {}  {}",
+        "-".repeat(name_width),
+        "-".repeat(CLSID_WIDTH),
+        "-".repeat(usability_width)
+    );
+    for (name, clsid, label) in rows {
+        println!("  {:<name_width$}  {clsid:<CLSID_WIDTH$}  {label}", truncate(&name, name_width));
+    }
+}
+
+/// `text` with its control characters replaced by visible escapes (`\n`, `\r`, `\t`, else
+/// `\x1b` and the like), so a stray newline or escape sequence in a registry value cannot break
+/// the listing, a TXT export or a CSV row. Returned unchanged with `raw` (`--raw-values`).
+pub fn escape_controls(text: &str, raw: bool) -> Cow<'_, str> {
+    if raw || !text.chars().any(char::is_control) {
+        return Cow::Borrowed(text);
+    }
+    let mut escaped = String::with_capacity(text.len() + 8);
+    for c in text.chars() {
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\x{:02x}", u32::from(c))),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// `text` cut to `width` characters, the last one an ellipsis, if it is longer
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+/// Print one object of the detailed listing. Level 3 also prints unset fields and
+/// cleared flags, so the output shows exactly what the scan captured.
+fn print_object_details(obj: &ComObject, verbose: u8, options: &ExportOptions) {
+    let all = verbose >= 3;
+    let usability = &*options.usability;
+    let raw = options.raw_values;
+    let show = |label: &str, value: Option<&str>| match value {
+        Some(value) => println!("  {label}: {}", escape_controls(value, raw)),
+        None if all => println!("  {label}: (not set)"),
+        None => {}
+    };
+    // A string value, followed by its registry type with `--show-value-types`
+    let show_typed = |label: &str, value: Option<&str>, value_type: Option<u32>| {
+        match (value, value_type) {
+            (Some(value), Some(value_type)) if options.show_value_types => {
+                let value = escape_controls(value, raw);
+                println!("  {label}: {value} [{}]", value_type_name(value_type));
             }
-            if let Some(ref desc) = obj.description {
-                println!("  Description: {desc}");
+            _ => show(label, value),
+        }
+    };
+    let flag = |label: &str, value: bool| {
+        if value || all {
+            println!("  {label}: {}", if value { "yes" } else { "no" });
+        }
+    };
+
+    println!("CLSID: {}", escape_controls(&obj.clsid, raw));
+    println!("  Registry: {}", escape_controls(&obj.registry_path(), raw));
+    if all {
+        println!("  Root: {:?}", obj.root);
+    }
+    show("Scope", obj.scope.map(|scope| scope.to_string()).as_deref());
+    show_typed("ProgID", obj.prog_id.as_deref(), obj.value_types.prog_id);
+    show("Current ProgID (CurVer)", obj.resolved_prog_id.as_deref());
+    show_typed("Description", obj.description.as_deref(), obj.value_types.description);
+    if obj.signer.is_some() || all {
+        show("Signer", obj.signer.as_deref());
+    }
+    if obj.company_name.is_some() || all {
+        show("Company", obj.company_name.as_deref());
+    }
+    if obj.product_name.is_some() || all {
+        show("Product", obj.product_name.as_deref());
+    }
+    for (name, value) in &obj.extra {
+        println!("  {}: {}", escape_controls(name, raw), escape_controls(value, raw));
+    }
+
+    if verbose >= 2 {
+        let value_types = &obj.value_types;
+        show_typed("InprocServer32", obj.inproc_server.as_deref(), value_types.inproc_server);
+        if obj.inproc_server.is_some() || all {
+            show("ThreadingModel", obj.threading_model.as_deref());
+        }
+        show_typed("LocalServer32", obj.local_server.as_deref(), value_types.local_server);
+        show("AppID", obj.app_id.as_deref());
+        show("RunAs", run_as_label(obj).as_deref());
+        show("TypeLib", obj.type_lib.as_deref());
+        show("TreatAs", obj.treat_as.as_deref());
+        if let Some(ref target) = obj.treat_as_target {
+            print_treat_as_target(target, options.raw_values);
+        }
+        if !obj.implemented_categories.is_empty() {
+            println!("  Implemented Categories:");
+            for category in &obj.implemented_categories {
+                println!("    {}", escape_controls(&category.to_string(), raw));
             }
+        } else if all {
+            println!("  Implemented Categories: (none)");
+        }
+        if !obj.shellex.is_empty() {
+            println!("  ShellEx Handlers: {}", escape_controls(&obj.shellex.join(", "), raw));
+        } else if all {
+            println!("  ShellEx Handlers: (none)");
+        }
+    }
 
-            // Check programmatic usability
-            let usability = check_usability(obj);
-            println!("  Programmatic Usability: {usability}");
-            println!();
+    if !obj.shell_handlers.is_empty() {
+        println!("  Shell Extension: {}", escape_controls(&obj.shell_handlers.join(", "), raw));
+    } else if all {
+        println!("  Shell Extension: (none)");
+    }
+    flag("Safe for Scripting", obj.safe_for_scripting);
+    flag("ActiveX Control", obj.is_control);
+    flag("Insertable", obj.is_insertable);
+    flag("Writable by Users", obj.writable_by_users);
+    flag("Hijackable Server Binary", obj.binary_hijackable);
+    flag("Unquoted Server Path", obj.unquoted_path);
+    flag("Currently Loaded", obj.currently_loaded);
+    if let Some(instantiable) = obj.instantiable {
+        println!("  Instantiable: {}", if instantiable { "yes" } else { "no" });
+    }
+    flag("Shadows Machine Registration", obj.shadowed);
+    if obj.suspicion_score > 0 {
+        println!("  Suspicion Score: {}", obj.suspicion_score);
+        for factor in suspicion_factors(obj) {
+            println!("    +{} {}", factor.weight, factor.name);
+        }
+    }
+    if all {
+        show("Last Write Time", obj.last_write_time.map(|t| t.to_rfc3339()).as_deref());
+    }
+
+    // Check programmatic usability
+    println!("  Programmatic Usability: {}", check_usability(obj, usability));
+    if all {
+        for line in usability.explain(obj) {
+            println!("    {line}");
         }
+    }
+}
+
+/// The `RunAs` identity as listed, marking servers that run as a fixed account
+fn run_as_label(obj: &ComObject) -> Option<String> {
+    let identity = obj.run_as.as_deref()?;
+    Some(if obj.runs_as_fixed_account() {
+        format!("{identity} (fixed account)")
     } else {
-        // Just show ProgIDs in compact format
-        println!("--- COM Objects with ProgID ---");
-        for obj in sorted_objects.iter().filter(|o| o.prog_id.is_some()) {
-            if let Some(ref prog_id) = obj.prog_id {
-                println!("  {} ({})", prog_id, obj.clsid);
+        identity.to_string()
+    })
+}
+
+/// Print every ProgID registered under more than one CLSID, which points to a broken
+/// installation or a hijacked registration
+pub fn report_duplicate_prog_ids(objects: &HashMap<String, ComObject>, raw: bool) {
+    let duplicates = duplicate_prog_ids(objects);
+    println!("--- Duplicate ProgIDs ---");
+    if duplicates.is_empty() {
+        println!("No ProgID is claimed by more than one CLSID.\n");
+        return;
+    }
+
+    for (prog_id, clsids) in &duplicates {
+        println!("{} ({} CLSIDs)", escape_controls(prog_id, raw), clsids.len());
+        for clsid in clsids {
+            println!("  {}", escape_controls(clsid, raw));
+        }
+    }
+    println!();
+}
+
+/// Print every scanned CLSID that several ProgIDs point to. Version-independent and versioned
+/// ProgIDs of one class (`App.Object` and `App.Object.2`) are expected; unrelated names
+/// pointing at the same class are worth a look.
+pub fn report_multi_prog_ids(
+    objects: &HashMap<String, ComObject>,
+    index: &[(String, String)],
+    raw: bool,
+) {
+    let multi = multi_prog_ids(objects, index);
+    println!("--- CLSIDs with Multiple ProgIDs ---");
+    if multi.is_empty() {
+        println!("No CLSID is referenced by more than one ProgID.\n");
+        return;
+    }
+
+    for (clsid, prog_ids) in &multi {
+        println!("{} ({} ProgIDs)", escape_controls(clsid, raw), prog_ids.len());
+        for prog_id in prog_ids {
+            println!("  {}", escape_controls(prog_id, raw));
+        }
+    }
+    println!();
+}
+
+/// Print the objects grouped by vendor with the number of objects per vendor, largest first.
+/// Objects without a vendor from the chosen source are listed under "Unknown".
+pub fn report_vendor_groups(
+    objects: &HashMap<String, ComObject>,
+    source: VendorSource,
+    options: &ExportOptions,
+) {
+    let groups = group_by(sort_objects(objects, options), |obj| source.vendor(obj));
+    println!("--- Objects by Vendor ({}) ---", source.label());
+    if groups.is_empty() {
+        println!("No objects to group.\n");
+        return;
+    }
+
+    for (vendor, members) in &groups {
+        println!("{} ({} objects)", escape_controls(vendor, options.raw_values), members.len());
+        for obj in members {
+            print_compact_line(obj, "  ", options.raw_values);
+        }
+    }
+    println!();
+}
+
+/// Write the vendor groups to a JSON file: one entry per vendor with its name, object count
+/// and objects (restricted to `--columns`), in the order of [`report_vendor_groups`]
+pub fn export_vendor_groups(
+    objects: &HashMap<String, ComObject>,
+    source: VendorSource,
+    path: &str,
+    options: &ExportOptions,
+) {
+    let export_result = interrupt::guard_write(|| {
+        let groups = group_by(sort_objects(objects, options), |obj| source.vendor(obj));
+        let records = groups
+            .into_iter()
+            .map(|(vendor, members)| {
+                let objects = members
+                    .iter()
+                    .map(|obj| json_record(obj, options))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(serde_json::json!({
+                    "vendor": vendor,
+                    "count": objects.len(),
+                    "objects": objects,
+                }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        if options.json_compact {
+            serde_json::to_writer(&mut writer, &records)?;
+        } else {
+            serde_json::to_writer_pretty(&mut writer, &records)?;
+        }
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        Ok(())
+    });
+    report_export_result(export_result, path, "json", false);
+}
+
+/// Write the `--stats-json` metrics document, honoring `--json-compact`
+pub fn export_stats_json(path: &str, metrics: &ScanMetrics, options: &ExportOptions) {
+    let export_result = interrupt::guard_write(|| {
+        let mut writer = BufWriter::new(File::create(path)?);
+        if options.json_compact {
+            serde_json::to_writer(&mut writer, metrics)?;
+        } else {
+            serde_json::to_writer_pretty(&mut writer, metrics)?;
+        }
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        Ok(())
+    });
+    report_export_result(export_result, path, "json", false);
+}
+
+/// Print the fields that differ between two registry views' registrations of the same CLSID.
+/// Each view is given as its name and the objects it returned before merging.
+pub fn report_view_conflicts(
+    (first_name, first): &(&str, HashMap<String, ComObject>),
+    (second_name, second): &(&str, HashMap<String, ComObject>),
+    raw: bool,
+) {
+    let conflicts = view_conflicts(first, second);
+    println!("--- {first_name} / {second_name} View Conflicts ---");
+    if conflicts.is_empty() {
+        println!("The CLSIDs registered in both views agree.\n");
+        return;
+    }
+
+    let mut current = None;
+    for conflict in &conflicts {
+        if current != Some(&conflict.clsid) {
+            println!("CLSID: {}", escape_controls(&conflict.clsid, raw));
+            current = Some(&conflict.clsid);
+        }
+        let value = |value: &Option<String>| match value {
+            Some(value) => escape_controls(value, raw).into_owned(),
+            None => "(not set)".to_string(),
+        };
+        println!("  {}:", conflict.field);
+        println!("    {first_name}: {}", value(&conflict.first));
+        println!("    {second_name}: {}", value(&conflict.second));
+    }
+    println!();
+}
+
+/// Print the deviations from a `--baseline`, grouped by status
+pub fn report_baseline_deviations(
+    path: &str,
+    baseline: &Baseline,
+    deviations: &[Deviation],
+    options: &ExportOptions,
+) {
+    let (color, raw) = (options.color, options.raw_values);
+    print!("{}", format_baseline_deviations(path, baseline.entries.len(), deviations, color, raw));
+}
+
+/// Render the `--baseline` report: unexpected objects in green, removed ones in red, and
+/// changed ones in yellow with each changed field as `old → new`. Without `color` the text is
+/// the same, without escape sequences. Changed values are quoted with their control characters
+/// escaped; ProgIDs are escaped unless `raw`.
+fn format_baseline_deviations(
+    path: &str,
+    baseline_len: usize,
+    deviations: &[Deviation],
+    color: bool,
+    raw: bool,
+) -> String {
+    let paint = |code: &str, text: String| {
+        if color {
+            format!("\x1b[{code}m{text}\x1b[0m")
+        } else {
+            text
+        }
+    };
+    let mut output = format!("--- Baseline Comparison ({path}, {baseline_len} objects) ---\n");
+    if deviations.is_empty() {
+        output.push_str("No deviations: the scan matches the baseline.\n\n");
+        return output;
+    }
+
+    let statuses =
+        [DeviationStatus::Unexpected, DeviationStatus::Removed, DeviationStatus::Changed];
+    for status in statuses {
+        let group: Vec<&Deviation> = deviations.iter().filter(|d| d.status == status).collect();
+        if group.is_empty() {
+            continue;
+        }
+        let code = match status {
+            DeviationStatus::Unexpected => ANSI_GREEN,
+            DeviationStatus::Removed => ANSI_RED,
+            DeviationStatus::Changed => ANSI_YELLOW,
+        };
+        output.push_str(&paint(code, format!("{} ({}):", status.label(), group.len())));
+        output.push('\n');
+        for deviation in group {
+            let clsid = escape_controls(&deviation.clsid, raw);
+            let line = match deviation.prog_id {
+                Some(ref prog_id) => format!("  {clsid} {}", escape_controls(prog_id, raw)),
+                None => format!("  {clsid}"),
+            };
+            output.push_str(&paint(code, line));
+            output.push('\n');
+            for change in &deviation.changes {
+                let value = |value: &Option<String>| {
+                    value.as_deref().map_or("(not set)".to_string(), |v| format!("{v:?}"))
+                };
+                let change_text = format!("{} → {}", value(&change.old), value(&change.new));
+                output.push_str(&format!("    {}: {}\n", change.field, paint(code, change_text)));
             }
         }
     }
+    output.push('\n');
+    output
+}
 
-    Ok(())
+/// Warn about registry keys and values that could not be read during the scan, so an incomplete
+/// result set is not mistaken for a complete one. `denied_values` holds the number of denied
+/// values of each scanned view; without it only their total is reported.
+pub fn report_inaccessible_keys(stats: &ScanStats, denied_values: &[(&str, usize)]) {
+    if stats.access_denied > 0 {
+        eprintln!(
+            "{} keys were inaccessible (run elevated for a complete scan)",
+            stats.access_denied
+        );
+    }
+    if stats.open_errors > 0 {
+        eprintln!("{} keys could not be opened due to other errors", stats.open_errors);
+    }
+    for (view_name, denied) in denied_values.iter().filter(|(_, denied)| *denied > 0) {
+        eprintln!("{denied} values skipped due to access denied in the {view_name} view");
+    }
+    if denied_values.is_empty() && stats.values_denied > 0 {
+        eprintln!("{} values skipped due to access denied", stats.values_denied);
+    }
+    if stats.access_denied > 0 || stats.open_errors > 0 || stats.values_denied > 0 {
+        eprintln!();
+    }
 }
 
 /// Prompt user for export options and perform export
-pub fn prompt_export(objects: &HashMap<String, ComObject>) -> Result<()> {
+pub fn prompt_export(
+    objects: &HashMap<String, ComObject>,
+    stats: &ScanStats,
+    options: &ExportOptions,
+) -> Result<()> {
+    let (format, path) = match prompt_export_target(EXPORT_FORMATS)? {
+        Some(target) => target,
+        None => return Ok(()),
+    };
+
+    // Handle errors instead of using '?'
+    // This prevents the program from exiting immediately on "Access Denied" errors
+    let export_result =
+        interrupt::guard_write(|| export_to_file(&format, objects, stats, &path, options));
+
+    report_export_result(export_result, &path, &format, options.open);
+    Ok(())
+}
+
+/// Export to the given path in the `--format` format without prompting, reporting the outcome
+pub fn export_to_path(
+    objects: &HashMap<String, ComObject>,
+    stats: &ScanStats,
+    path: &str,
+    options: &ExportOptions,
+) {
+    let export_result =
+        interrupt::guard_write(|| export_to_file(&options.format, objects, stats, path, options));
+    report_export_result(export_result, path, &options.format, options.open);
+}
+
+/// Write the export in the given format to a file.
+/// TXT, CSV and TSV files get the UTF-8 BOM when `--bom` is set.
+fn export_to_file(
+    format: &str,
+    objects: &HashMap<String, ComObject>,
+    stats: &ScanStats,
+    path: &str,
+    options: &ExportOptions,
+) -> Result<()> {
+    // Workbooks are binary and written by the xlsx writer itself
+    if format == "xlsx" {
+        return export_xlsx(objects, path, options);
+    }
+
+    let mut file = File::create(path)?;
+    if options.bom && matches!(format, "txt" | "csv" | "tsv") {
+        file.write_all(UTF8_BOM)?;
+    }
+
+    let mut writer = BufWriter::new(file);
+    write_export(format, objects, stats, &mut writer, options)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Copy the export in the `--format` format to the clipboard instead of writing a file.
+/// Returns the number of bytes copied.
+pub fn copy_export_to_clipboard(
+    objects: &HashMap<String, ComObject>,
+    stats: &ScanStats,
+    options: &ExportOptions,
+) -> Result<usize> {
+    let mut buffer = Vec::new();
+    write_export(&options.format, objects, stats, &mut buffer, options)?;
+    let text = String::from_utf8(buffer)?;
+    clipboard::set_text(&text)?;
+    Ok(text.len())
+}
+
+/// Render the export in the given format
+fn write_export(
+    format: &str,
+    objects: &HashMap<String, ComObject>,
+    stats: &ScanStats,
+    writer: &mut dyn Write,
+    options: &ExportOptions,
+) -> Result<()> {
+    match format {
+        "txt" => export_txt(objects, stats, writer, options),
+        "csv" => export_csv(objects, writer, b',', options),
+        "tsv" => export_csv(objects, writer, b'\t', options),
+        "json" => export_json(objects, writer, options),
+        "html" => export_html(objects, stats, writer, options),
+        "siem" => export_siem(objects, writer, options),
+        "xlsx" => Err(anyhow::anyhow!("xlsx workbooks can only be written to a file")),
+        _ => export_jsonl(objects, writer, options),
+    }
+}
+
+/// Ask whether to export, in which of the supported formats, and to which path.
+/// Returns `None` if the user declines, picks an invalid format, or presses Ctrl+C.
+fn prompt_export_target(formats: &[&str]) -> Result<Option<(String, String)>> {
+    if interrupt::is_interrupted() {
+        return Ok(None);
+    }
+
     println!("Do you want to export the results? (y/n): ");
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input)?;
-    if input.trim().to_lowercase() != "y" {
-        return Ok(());
+    let Some(input) = interrupt::read_line()? else {
+        return Ok(None);
+    };
+    if input.to_lowercase() != "y" {
+        return Ok(None);
     }
 
-    println!("Export format (txt/csv): ");
-    let mut format_input = String::new();
-    std::io::stdin().read_line(&mut format_input)?;
-    let format = format_input.trim().to_lowercase();
-    if format != "txt" && format != "csv" {
+    println!("Export format ({}): ", formats.join("/"));
+    let Some(format_input) = interrupt::read_line()? else {
+        return Ok(None);
+    };
+    let format = format_input.to_lowercase();
+    if !formats.contains(&format.as_str()) {
         println!("Invalid format, skipping export.");
-        return Ok(());
+        return Ok(None);
     }
 
     println!("Enter file path to export to (Press Enter for 'results.{}'): ", format);
-    let mut path_input = String::new();
-    std::io::stdin().read_line(&mut path_input)?;
-    let trimmed = path_input.trim();
+    let Some(path_input) = interrupt::read_line()? else {
+        return Ok(None);
+    };
 
-    let path = if trimmed.is_empty() {
+    let path = if path_input.is_empty() {
         format!("results.{}", format)
     } else {
-        trimmed.to_string()
+        path_input
     };
 
-    // Use a match block to handle errors instead of '?'
-    // This prevents the program from exiting immediately on "Access Denied" errors
-    let export_result = if format == "txt" {
-        export_txt(objects, &path)
-    } else {
-        export_csv(objects, &path)
-    };
+    Ok(Some((format, path)))
+}
 
+/// Print the outcome of an export, with a hint for the common access-denied case.
+/// A failed export removes its partially written file; a successful one is opened if `open`
+/// is set, which only the main export passes on from `--open`.
+fn report_export_result(export_result: Result<()>, path: &str, format: &str, open: bool) {
     match export_result {
-        Ok(_) => println!("Successfully exported to {}", path),
+        Ok(_) => {
+            println!("Successfully exported to {}", path);
+            if open {
+                if let Err(e) = shell::open_file(path) {
+                    println!("Could not open the exported file: {}", e);
+                }
+            }
+        }
         Err(e) => {
+            let _ = std::fs::remove_file(path);
             println!("\n❌ Export failed: {}", e);
             println!("Hint: If running as Administrator, writing to the default folder (System32) is restricted.");
             println!("      Try providing a full absolute path (e.g., C:\\Temp\\results.{})", format);
         }
     }
+}
 
-    Ok(())
+/// Create a TXT, CSV or TSV export file, writing the UTF-8 BOM first if requested.
+/// Legacy tools such as older Excel versions only detect UTF-8 when the BOM is present.
+/// JSON files must not start with a BOM, so they are created with [`File::create`].
+fn create_text_file(path: &str, options: &ExportOptions) -> std::io::Result<File> {
+    let mut file = File::create(path)?;
+    if options.bom {
+        file.write_all(UTF8_BOM)?;
+    }
+    Ok(file)
 }
 
-/// Export results to a text file
-fn export_txt(objects: &HashMap<String, ComObject>, path: &str) -> Result<()> {
+/// Export results as the detailed text listing, with control characters in the values
+/// escaped unless `--raw-values`
+fn export_txt(
+    objects: &HashMap<String, ComObject>,
+    stats: &ScanStats,
+    writer: &mut dyn Write,
+    options: &ExportOptions,
+) -> Result<()> {
+    let escape = |text: &str| escape_controls(text, options.raw_values).into_owned();
     let mut output = String::new();
     output.push_str("=== Results ===\n");
+    output.push_str(&format!(
+        "Scanned {} CLSIDs, matched {}\n",
+        stats.enumerated, stats.matched
+    ));
     output.push_str(&format!("Total unique COM objects found: {}\n\n", objects.len()));
 
     if objects.is_empty() {
         output.push_str("No COM objects found matching the criteria.\n");
     } else {
-        // Sort by ProgID
-        let mut sorted_objects: Vec<_> = objects.values().collect();
-        sorted_objects.sort_by(|a, b| {
-            match (&a.prog_id, &b.prog_id) {
-                (Some(pa), Some(pb)) => pa.cmp(pb),
-                (Some(_), None) => std::cmp::Ordering::Less,
-                (None, Some(_)) => std::cmp::Ordering::Greater,
-                (None, None) => a.clsid.cmp(&b.clsid),
-            }
-        });
+        let sorted_objects = sort_objects(objects, options);
 
-        let with_progid = sorted_objects
-            .iter()
-            .filter(|obj| obj.prog_id.is_some())
-            .count();
+        // The TXT export does not break the counts down by registry view
+        let summary = compute_summary(objects, &[]);
         output.push_str(&format!(
             "COM objects with ProgID: {} ({:.1}%)\n",
-            with_progid,
-            (with_progid as f64 / objects.len() as f64) * 100.0
-        ));
-        output.push_str(&format!(
-            "COM objects without ProgID: {}\n\n",
-            objects.len() - with_progid
+            summary.with_prog_id,
+            summary.prog_id_percent()
         ));
+        output.push_str(&format!("COM objects without ProgID: {}\n", summary.without_prog_id()));
+        output.push_str(&format!("ActiveX controls: {}\n\n", summary.controls));
         output.push_str("--- Detailed Listing ---\n\n");
 
         for obj in sorted_objects {
-            output.push_str(&format!("CLSID: {}\n", obj.clsid));
+            output.push_str(&format!("CLSID: {}\n", escape(&obj.clsid)));
+            output.push_str(&format!("  Registry: {}\n", escape(&obj.registry_path())));
+            if let Some(scope) = obj.scope {
+                output.push_str(&format!("  Scope: {}\n", scope));
+            }
             if let Some(ref prog_id) = obj.prog_id {
-                output.push_str(&format!("  ProgID: {}\n", prog_id));
+                output.push_str(&format!("  ProgID: {}\n", escape(prog_id)));
+            }
+            if let Some(ref resolved) = obj.resolved_prog_id {
+                output.push_str(&format!("  Current ProgID (CurVer): {}\n", escape(resolved)));
             }
             if let Some(ref desc) = obj.description {
-                output.push_str(&format!("  Description: {}\n", desc));
+                output.push_str(&format!("  Description: {}\n", escape(desc)));
+            }
+            if let Some(ref server) = obj.inproc_server {
+                output.push_str(&format!("  InprocServer32: {}\n", escape(server)));
+            }
+            if let Some(ref model) = obj.threading_model {
+                output.push_str(&format!("  ThreadingModel: {}\n", escape(model)));
+            }
+            if let Some(ref server) = obj.local_server {
+                output.push_str(&format!("  LocalServer32: {}\n", escape(server)));
+            }
+            if let Some(ref app_id) = obj.app_id {
+                output.push_str(&format!("  AppID: {}\n", escape(app_id)));
+            }
+            if let Some(run_as) = run_as_label(obj) {
+                output.push_str(&format!("  RunAs: {}\n", escape(&run_as)));
+            }
+            if let Some(ref type_lib) = obj.type_lib {
+                output.push_str(&format!("  TypeLib: {}\n", escape(type_lib)));
             }
-            let usability = check_usability(obj);
+            if let Some(ref treat_as) = obj.treat_as {
+                output.push_str(&format!("  TreatAs: {}\n", escape(treat_as)));
+            }
+            if let Some(ref target) = obj.treat_as_target {
+                output.push_str(&format!("  TreatAs Target: {}\n", escape(&target.to_string())));
+            }
+            if let Some(ref signer) = obj.signer {
+                output.push_str(&format!("  Signer: {}\n", escape(signer)));
+            }
+            if let Some(ref company) = obj.company_name {
+                output.push_str(&format!("  Company: {}\n", escape(company)));
+            }
+            if let Some(ref product) = obj.product_name {
+                output.push_str(&format!("  Product: {}\n", escape(product)));
+            }
+            for (name, value) in &obj.extra {
+                output.push_str(&format!("  {}: {}\n", escape(name), escape(value)));
+            }
+            if !obj.implemented_categories.is_empty() {
+                output.push_str("  Implemented Categories:\n");
+                for category in &obj.implemented_categories {
+                    output.push_str(&format!("    {}\n", escape(&category.to_string())));
+                }
+            }
+            if !obj.shellex.is_empty() {
+                let handlers = escape(&obj.shellex.join(", "));
+                output.push_str(&format!("  ShellEx Handlers: {}\n", handlers));
+            }
+            if !obj.shell_handlers.is_empty() {
+                let handlers = obj.shell_handlers.join(", ");
+                output.push_str(&format!("  Shell Extension: {}\n", escape(&handlers)));
+            }
+            if obj.safe_for_scripting {
+                output.push_str("  Safe for Scripting: yes\n");
+            }
+            if obj.is_control {
+                output.push_str("  ActiveX Control: yes\n");
+            }
+            if obj.is_insertable {
+                output.push_str("  Insertable: yes\n");
+            }
+            if obj.writable_by_users {
+                output.push_str("  Writable by Users: yes\n");
+            }
+            if obj.binary_hijackable {
+                output.push_str("  Hijackable Server Binary: yes\n");
+            }
+            if obj.unquoted_path {
+                output.push_str("  Unquoted Server Path: yes\n");
+            }
+            if obj.currently_loaded {
+                output.push_str("  Currently Loaded: yes\n");
+            }
+            if let Some(instantiable) = obj.instantiable {
+                let answer = if instantiable { "yes" } else { "no" };
+                output.push_str(&format!("  Instantiable: {answer}\n"));
+            }
+            if obj.shadowed {
+                output.push_str("  Shadows Machine Registration: yes\n");
+            }
+            if obj.suspicion_score > 0 {
+                output.push_str(&format!("  Suspicion Score: {}\n", obj.suspicion_score));
+                for factor in suspicion_factors(obj) {
+                    output.push_str(&format!("    +{} {}\n", factor.weight, factor.name));
+                }
+            }
+            if let Some(written) = obj.last_write_time {
+                output.push_str(&format!("  Last Write Time: {}\n", written.to_rfc3339()));
+            }
+            let usability = check_usability(obj, &*options.usability);
             output.push_str(&format!("  Programmatic Usability: {}\n\n", usability));
         }
     }
 
-    let mut file = File::create(path)?;
-    file.write_all(output.as_bytes())?;
+    writer.write_all(output.as_bytes())?;
     Ok(())
 }
 
-/// Export results to a CSV file
-fn export_csv(objects: &HashMap<String, ComObject>, path: &str) -> Result<()> {
-    let mut wtr = Writer::from_writer(File::create(path)?);
-    wtr.write_record(["CLSID", "ProgID", "Description", "Usability"])?;
+/// Export results as delimited text: CSV with `,` or TSV with `\t`, using the same columns
+fn export_csv(
+    objects: &HashMap<String, ComObject>,
+    writer: &mut dyn Write,
+    delimiter: u8,
+    options: &ExportOptions,
+) -> Result<()> {
+    let mut wtr = WriterBuilder::new().delimiter(delimiter).from_writer(writer);
+    let columns = tabular_columns(options, CSV_COLUMNS.to_vec());
+    wtr.write_record(&columns)?;
+
+    let sorted_objects = sort_objects(objects, options);
+
+    for obj in sorted_objects {
+        let values = columns.iter().map(|column| {
+            let value = column_value(obj, column, &*options.usability);
+            escape_controls(&value, options.raw_values).into_owned()
+        });
+        wtr.write_record(values)?;
+    }
+
+    wtr.flush()?;
+    Ok(())
+}
+
+/// The columns of a CSV/TSV, HTML or XLSX export: the `--columns` selection or the given
+/// defaults, followed by the `--extra-value` columns
+fn tabular_columns<'a>(options: &'a ExportOptions, defaults: Vec<&'a str>) -> Vec<&'a str> {
+    let mut columns = match options.columns {
+        Some(ref columns) => columns.iter().map(String::as_str).collect(),
+        None => defaults,
+    };
+    columns.extend(options.extra_columns.iter().map(String::as_str));
+    columns
+}
+
+/// Export results as an Excel workbook: one worksheet with a bold, frozen header row and an
+/// auto-filter over all columns. Without `--columns` every field becomes a column. The
+/// worksheet runs in constant memory mode, so rows are streamed to a temporary file as they
+/// are written instead of being kept in memory until the workbook is saved.
+fn export_xlsx(
+    objects: &HashMap<String, ComObject>,
+    path: &str,
+    options: &ExportOptions,
+) -> Result<()> {
+    let all_columns = EXPORT_COLUMNS.iter().map(|(column, _)| *column).collect();
+    let columns = tabular_columns(options, all_columns);
+    let sorted_objects = sort_objects(objects, options);
+
+    let mut workbook = Workbook::new();
+    let header = Format::new().set_bold();
+    let worksheet = workbook.add_worksheet_with_constant_memory();
+    worksheet.set_name("COM Objects")?;
+    worksheet.set_freeze_panes(1, 0)?;
+    let last_column = columns.len().saturating_sub(1) as u16;
+    worksheet.autofilter(0, 0, sorted_objects.len() as u32, last_column)?;
+
+    for (col, column) in columns.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *column, &header)?;
+    }
+    for (row, obj) in sorted_objects.into_iter().enumerate() {
+        for (col, column) in columns.iter().enumerate() {
+            let value = column_value(obj, column, &*options.usability);
+            if !value.is_empty() {
+                worksheet.write_string(row as u32 + 1, col as u16, value)?;
+            }
+        }
+    }
+
+    workbook.save(path)?;
+    Ok(())
+}
+
+/// Export results as a self-contained HTML report: a table with sortable columns, a live
+/// search box across all columns, and a usability filter, driven by embedded vanilla JS.
+/// Every cell value is HTML-escaped.
+fn export_html(
+    objects: &HashMap<String, ComObject>,
+    stats: &ScanStats,
+    writer: &mut dyn Write,
+    options: &ExportOptions,
+) -> Result<()> {
+    let columns = tabular_columns(options, CSV_COLUMNS.to_vec());
+
+    let mut output = String::new();
+    output.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    output.push_str("<title>COM Objects</title>\n");
+    output.push_str(HTML_STYLE);
+    output.push_str("</head>\n<body>\n<h1>COM Objects</h1>\n");
+    output.push_str(&format!(
+        "<p>Scanned {} CLSIDs, matched {}. Total unique COM objects found: {}</p>\n",
+        stats.enumerated,
+        stats.matched,
+        objects.len()
+    ));
+
+    output.push_str("<div class=\"controls\">\n");
+    output.push_str("<input id=\"search\" type=\"search\" placeholder=\"Search all columns...\">\n");
+    output.push_str("<select id=\"usability\">\n<option value=\"\">All usability levels</option>\n");
+    for level in UsabilityLevel::value_variants().iter().rev() {
+        output.push_str(&format!(
+            "<option value=\"{}\">{}</option>\n",
+            usability_key(*level),
+            html_escape(options.usability.label(*level))
+        ));
+    }
+    output.push_str("</select>\n<span id=\"count\"></span>\n</div>\n");
+
+    output.push_str("<table id=\"results\">\n<thead>\n<tr>");
+    for column in &columns {
+        output.push_str(&format!("<th>{}</th>", html_escape(column)));
+    }
+    output.push_str("</tr>\n</thead>\n<tbody>\n");
+
+    for obj in sort_objects(objects, options) {
+        output.push_str(&format!(
+            "<tr data-usability=\"{}\">",
+            usability_key(options.usability.score(obj).level)
+        ));
+        for column in &columns {
+            let value = column_value(obj, column, &*options.usability);
+            output.push_str(&format!("<td>{}</td>", html_escape(&value)));
+        }
+        output.push_str("</tr>\n");
+    }
+    output.push_str("</tbody>\n</table>\n");
+    output.push_str(HTML_SCRIPT);
+    output.push_str("</body>\n</html>\n");
+
+    writer.write_all(output.as_bytes())?;
+    Ok(())
+}
+
+/// Stable identifier of a usability level, used by the HTML report's usability filter
+fn usability_key(level: UsabilityLevel) -> String {
+    level
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// Escape text for use in HTML element content and attribute values
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Styles embedded in the HTML report
+const HTML_STYLE: &str = r#"<style>
+body { font-family: "Segoe UI", sans-serif; margin: 1.5em; }
+.controls { margin: 1em 0; display: flex; gap: 0.75em; align-items: center; }
+#search { width: 24em; padding: 0.3em; }
+table { border-collapse: collapse; width: 100%; font-size: 0.9em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.5em; text-align: left; vertical-align: top; }
+th { background: #f0f0f0; cursor: pointer; user-select: none; position: sticky; top: 0; }
+tbody tr:nth-child(even) { background: #fafafa; }
+</style>
+"#;
+
+/// Search, usability filter, and column sorting for the HTML report
+const HTML_SCRIPT: &str = r#"<script>
+(function () {
+  var table = document.getElementById("results");
+  var tbody = table.tBodies[0];
+  var rows = Array.prototype.slice.call(tbody.rows);
+  var search = document.getElementById("search");
+  var usability = document.getElementById("usability");
+  var count = document.getElementById("count");
+
+  function applyFilters() {
+    var needle = search.value.toLowerCase();
+    var level = usability.value;
+    var visible = 0;
+    rows.forEach(function (row) {
+      var show = (!level || row.getAttribute("data-usability") === level) &&
+        (!needle || row.textContent.toLowerCase().indexOf(needle) !== -1);
+      row.style.display = show ? "" : "none";
+      if (show) {
+        visible++;
+      }
+    });
+    count.textContent = visible + " of " + rows.length + " objects";
+  }
+
+  var sortColumn = -1;
+  var ascending = true;
+  Array.prototype.forEach.call(table.tHead.rows[0].cells, function (header, index) {
+    header.addEventListener("click", function () {
+      ascending = sortColumn === index ? !ascending : true;
+      sortColumn = index;
+      rows.sort(function (a, b) {
+        var x = a.cells[index].textContent;
+        var y = b.cells[index].textContent;
+        return ascending ? x.localeCompare(y) : y.localeCompare(x);
+      });
+      rows.forEach(function (row) {
+        tbody.appendChild(row);
+      });
+    });
+  });
+
+  search.addEventListener("input", applyFilters);
+  usability.addEventListener("change", applyFilters);
+  applyFilters();
+})();
+</script>
+"#;
+
+/// Export results as a single JSON array, pretty-printed unless compact output was requested
+fn export_json(
+    objects: &HashMap<String, ComObject>,
+    mut writer: &mut dyn Write,
+    options: &ExportOptions,
+) -> Result<()> {
+    let records = sort_objects(objects, options)
+        .into_iter()
+        .map(|obj| json_record(obj, options))
+        .collect::<Result<Vec<_>>>()?;
+
+    if options.json_compact {
+        serde_json::to_writer(&mut writer, &records)?;
+    } else {
+        serde_json::to_writer_pretty(&mut writer, &records)?;
+    }
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Export results as JSON Lines: one independently parseable JSON object per line
+fn export_jsonl(
+    objects: &HashMap<String, ComObject>,
+    writer: &mut dyn Write,
+    options: &ExportOptions,
+) -> Result<()> {
+    let sorted_objects = sort_objects(objects, options);
+
+    for obj in sorted_objects {
+        write_jsonl_record(writer, obj, options)?;
+    }
+
+    Ok(())
+}
+
+/// Export results as SIEM events: newline-delimited JSON with flat, dotted field names in the
+/// style of the Elastic Common Schema, one `com_object` state event per object.
+/// Fields without a value are omitted; `--columns` does not apply.
+///
+/// | Field                   | Source                                                  |
+/// |-------------------------|---------------------------------------------------------|
+/// | `@timestamp`            | Export time (RFC 3339, UTC)                             |
+/// | `event.kind`            | Always `state` (a snapshot, not an activity)            |
+/// | `event.category`        | Always `configuration`                                  |
+/// | `event.type`            | Always `info`                                           |
+/// | `event.dataset`         | Always `ole_inspector.com_object`                       |
+/// | `registry.hive`         | Hive of the CLSID key, e.g. `HKEY_LOCAL_MACHINE`        |
+/// | `registry.key`          | Key below the hive, e.g. `SOFTWARE\Classes\CLSID\{...}` |
+/// | `registry.path`         | Full CLSID key path (`Registry` in the listing)         |
+/// | `file.path`             | Server binary: InprocServer32 if set, else LocalServer32 |
+/// | `dll.path`              | InprocServer32 binary                                   |
+/// | `process.executable`    | LocalServer32 binary                                    |
+/// | `process.command_line`  | LocalServer32 value as registered                       |
+/// | `file.code_signature.subject_name` | Signer of the server binary                  |
+/// | `file.pe.company`, `file.pe.product` | CompanyName and ProductName of its version info |
+/// | `com.clsid`             | CLSID                                                   |
+/// | `com.prog_id`           | ProgID                                                  |
+/// | `com.current_prog_id`   | ProgID reached through `CurVer`                         |
+/// | `com.description`       | Description                                             |
+/// | `com.threading_model`   | `ThreadingModel` of the InprocServer32                  |
+/// | `com.app_id`            | AppID                                                   |
+/// | `com.run_as`            | `RunAs` identity of the AppID                           |
+/// | `com.type_lib`          | LIBID of the class's type library                       |
+/// | `com.treat_as`          | CLSID the `TreatAs` subkey redirects to                 |
+/// | `com.treat_as_target.*` | `clsid`, `prog_id`, `description` of the resolved class |
+/// | `com.scope`             | `user`, `machine` or `both`                             |
+/// | `com.categories`        | Implemented category GUIDs (array)                      |
+/// | `com.usability`         | Usability level, e.g. `very-low` or `high`              |
+/// | `com.shell_handlers`    | Shell extension handler types (array)                   |
+/// | `com.shellex`           | Handler types below the CLSID's own `shellex` key (array) |
+/// | `com.extra.<name>`      | Each `--extra-value` that is set                        |
+/// | `com.safe_for_scripting`, `com.control`, `com.insertable` | Flags             |
+/// | `com.last_write_time`   | Last write time of the CLSID key                        |
+/// | `com.writable_by_users`, `com.binary_hijackable`, `com.unquoted_path` | Audit flags |
+/// | `com.currently_loaded`  | InprocServer32 DLL loaded in a running process          |
+/// | `com.instantiable`      | `--probe` created the class with CoCreateInstance       |
+/// | `com.shadowed`          | Per-user registration overrides the machine-wide server |
+/// | `risk.calculated_score` | `--suspicious` score                                    |
+fn export_siem(
+    objects: &HashMap<String, ComObject>,
+    writer: &mut dyn Write,
+    options: &ExportOptions,
+) -> Result<()> {
+    let timestamp = DateTime::<Utc>::from(std::time::SystemTime::now()).to_rfc3339();
+
+    for obj in sort_objects(objects, options) {
+        let mut event = serde_json::Map::new();
+        let mut set = |key: &str, value: serde_json::Value| {
+            if !value.is_null() {
+                event.insert(key.to_string(), value);
+            }
+        };
+        let inproc_binary = obj.inproc_server.as_deref().and_then(server_binary);
+        let local_binary = obj.local_server.as_deref().and_then(server_binary);
+        let path_value = |path: &Option<PathBuf>| path.as_ref().map(|p| p.to_string_lossy()).into();
+
+        set("@timestamp", timestamp.as_str().into());
+        set("event.kind", "state".into());
+        set("event.category", "configuration".into());
+        set("event.type", "info".into());
+        set("event.dataset", "ole_inspector.com_object".into());
+        let registry_path = obj.registry_path();
+        let (hive, key) = registry_path.split_once('\\').unwrap_or_default();
+        set("registry.hive", hive.into());
+        set("registry.key", key.into());
+        set("registry.path", registry_path.as_str().into());
+        set("file.path", path_value(&inproc_binary.clone().or(local_binary.clone())));
+        set("dll.path", path_value(&inproc_binary));
+        set("process.executable", path_value(&local_binary));
+        set("process.command_line", obj.local_server.clone().into());
+        set("file.code_signature.subject_name", obj.signer.clone().into());
+        set("file.pe.company", obj.company_name.clone().into());
+        set("file.pe.product", obj.product_name.clone().into());
+        set("com.clsid", obj.clsid.clone().into());
+        set("com.prog_id", obj.prog_id.clone().into());
+        set("com.current_prog_id", obj.resolved_prog_id.clone().into());
+        set("com.description", obj.description.clone().into());
+        set("com.threading_model", obj.threading_model.clone().into());
+        set("com.app_id", obj.app_id.clone().into());
+        set("com.run_as", obj.run_as.clone().into());
+        set("com.type_lib", obj.type_lib.clone().into());
+        set("com.treat_as", obj.treat_as.clone().into());
+        if let Some(ref target) = obj.treat_as_target {
+            set("com.treat_as_target.clsid", target.clsid.clone().into());
+            set("com.treat_as_target.prog_id", target.prog_id.clone().into());
+            set("com.treat_as_target.description", target.description.clone().into());
+        }
+        let scope = obj.scope.map(|scope| format!("{scope:?}").to_lowercase());
+        set("com.scope", scope.into());
+        let categories: Vec<&str> =
+            obj.implemented_categories.iter().map(|c| c.catid.as_str()).collect();
+        if !categories.is_empty() {
+            set("com.categories", categories.into());
+        }
+        let usability = options.usability.score(obj).level.to_possible_value();
+        set("com.usability", usability.map(|value| value.get_name().to_string()).into());
+        if !obj.shell_handlers.is_empty() {
+            set("com.shell_handlers", obj.shell_handlers.clone().into());
+        }
+        if !obj.shellex.is_empty() {
+            set("com.shellex", obj.shellex.clone().into());
+        }
+        for (name, value) in &obj.extra {
+            set(&format!("com.extra.{name}"), value.as_str().into());
+        }
+        set("com.safe_for_scripting", obj.safe_for_scripting.into());
+        set("com.control", obj.is_control.into());
+        set("com.insertable", obj.is_insertable.into());
+        set("com.last_write_time", obj.last_write_time.map(|t| t.to_rfc3339()).into());
+        set("com.writable_by_users", obj.writable_by_users.into());
+        set("com.binary_hijackable", obj.binary_hijackable.into());
+        set("com.unquoted_path", obj.unquoted_path.into());
+        set("com.currently_loaded", obj.currently_loaded.into());
+        set("com.instantiable", obj.instantiable.into());
+        set("com.shadowed", obj.shadowed.into());
+        set("risk.calculated_score", obj.suspicion_score.into());
+
+        serde_json::to_writer(&mut *writer, &event)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Keep only the first `limit` objects in sort order (0 = no limit), so the cap applies
+/// to the merged result of all scanned views
+pub fn apply_limit(
+    objects: &mut HashMap<String, ComObject>,
+    limit: usize,
+    options: &ExportOptions,
+) {
+    if limit == 0 || objects.len() <= limit {
+        return;
+    }
 
+    let keep: HashSet<String> = sort_objects(objects, options)
+        .into_iter()
+        .take(limit)
+        .map(|obj| obj.clsid.clone())
+        .collect();
+    objects.retain(|clsid, _| keep.contains(clsid));
+}
+
+/// Collect the objects ordered by the chosen sort key. The direction is applied once here,
+/// so every output path lists objects in the same order.
+fn sort_objects<'a>(
+    objects: &'a HashMap<String, ComObject>,
+    options: &ExportOptions,
+) -> Vec<&'a ComObject> {
+    let sort = options.sort;
     let mut sorted_objects: Vec<_> = objects.values().collect();
     sorted_objects.sort_by(|a, b| {
-        match (&a.prog_id, &b.prog_id) {
-            (Some(pa), Some(pb)) => pa.cmp(pb),
-            (Some(_), None) => std::cmp::Ordering::Less,
-            (None, Some(_)) => std::cmp::Ordering::Greater,
-            (None, None) => a.clsid.cmp(&b.clsid),
+        let ordering = compare_objects(a, b, sort.key, &*options.usability);
+        if sort.descending {
+            ordering.reverse()
+        } else {
+            ordering
         }
     });
+    sorted_objects
+}
 
-    for obj in sorted_objects {
-        let usability = check_usability(obj);
-        wtr.write_record([
-            obj.clsid.as_str(),
-            obj.prog_id.as_deref().unwrap_or(""),
-            obj.description.as_deref().unwrap_or(""),
-            usability,
-        ])?;
+/// Ascending comparison for a sort key. Objects missing the key's value sort last,
+/// and usability sorts from most to least usable. Ties always fall back to the CLSID,
+/// making this a total order so repeated runs produce byte-identical exports.
+fn compare_objects(
+    a: &ComObject,
+    b: &ComObject,
+    key: SortKey,
+    usability: &dyn UsabilityScorer,
+) -> Ordering {
+    let ordering = match key {
+        SortKey::ProgId => compare_optional(&a.prog_id, &b.prog_id),
+        SortKey::Clsid => Ordering::Equal,
+        SortKey::Description => compare_optional(
+            &a.description.as_deref().map(str::to_lowercase),
+            &b.description.as_deref().map(str::to_lowercase),
+        ),
+        SortKey::Usability => usability.score(b).level.cmp(&usability.score(a).level),
+        SortKey::Suspicion => b.suspicion_score.cmp(&a.suspicion_score),
+    };
+    ordering.then_with(|| a.clsid.cmp(&b.clsid))
+}
+
+/// Compare optional values, placing missing ones after present ones
+fn compare_optional(a: &Option<String>, b: &Option<String>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Write a single object as one JSON line
+fn write_jsonl_record(
+    writer: &mut dyn Write,
+    obj: &ComObject,
+    options: &ExportOptions,
+) -> Result<()> {
+    serde_json::to_writer(&mut *writer, &json_record(obj, options)?)?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Serialize an object for JSON output, keeping only the selected columns' keys
+fn json_record(obj: &ComObject, options: &ExportOptions) -> Result<serde_json::Value> {
+    let full = serde_json::to_value(obj)?;
+    let Some(ref columns) = options.columns else {
+        return Ok(full);
+    };
+
+    let mut record = serde_json::Map::new();
+    for column in columns {
+        let Some((_, key)) = EXPORT_COLUMNS.iter().find(|(name, _)| name == column) else {
+            continue;
+        };
+        let value = if *key == "usability" {
+            serde_json::Value::from(check_usability(obj, &*options.usability))
+        } else {
+            full.get(*key).cloned().unwrap_or_default()
+        };
+        record.insert(key.to_string(), value);
+    }
+    // `--extra-value` values are kept whatever the selection, like the tabular exports
+    if !options.extra_columns.is_empty() {
+        record.insert("extra".into(), full.get("extra").cloned().unwrap_or_default());
+    }
+    Ok(serde_json::Value::Object(record))
+}
+
+/// Render one CSV/TSV cell for an export column
+fn column_value(obj: &ComObject, column: &str, usability: &dyn UsabilityScorer) -> String {
+    let text = |value: &Option<String>| value.clone().unwrap_or_default();
+    match column {
+        "CLSID" => obj.clsid.clone(),
+        "ProgID" => text(&obj.prog_id),
+        "Description" => text(&obj.description),
+        "Usability" => check_usability(obj, usability).to_string(),
+        "CurrentProgID" => text(&obj.resolved_prog_id),
+        "InprocServer32" => text(&obj.inproc_server),
+        "ThreadingModel" => text(&obj.threading_model),
+        "LocalServer32" => text(&obj.local_server),
+        "AppID" => text(&obj.app_id),
+        "RunAs" => text(&obj.run_as),
+        "TypeLib" => text(&obj.type_lib),
+        "TreatAs" => text(&obj.treat_as),
+        "TreatAsTarget" => obj.treat_as_target.as_ref().map(|t| t.to_string()).unwrap_or_default(),
+        "Root" => obj.root.path().to_string(),
+        "Scope" => obj.scope.map(|scope| scope.to_string()).unwrap_or_default(),
+        "ImplementedCategories" => obj
+            .implemented_categories
+            .iter()
+            .map(|category| category.to_string())
+            .collect::<Vec<_>>()
+            .join("; "),
+        "SafeForScripting" => obj.safe_for_scripting.to_string(),
+        "Control" => obj.is_control.to_string(),
+        "Insertable" => obj.is_insertable.to_string(),
+        "LastWriteTime" => obj.last_write_time.map(|t| t.to_rfc3339()).unwrap_or_default(),
+        "WritableByUsers" => obj.writable_by_users.to_string(),
+        "BinaryHijackable" => obj.binary_hijackable.to_string(),
+        "UnquotedPath" => obj.unquoted_path.to_string(),
+        "SuspicionScore" => obj.suspicion_score.to_string(),
+        "CurrentlyLoaded" => obj.currently_loaded.to_string(),
+        "Instantiable" => obj.instantiable.map(|created| created.to_string()).unwrap_or_default(),
+        "Shadowed" => obj.shadowed.to_string(),
+        "Signer" => text(&obj.signer),
+        "CompanyName" => text(&obj.company_name),
+        "ProductName" => text(&obj.product_name),
+        "ShellHandlers" => obj.shell_handlers.join("; "),
+        "ShellEx" => obj.shellex.join("; "),
+        _ => column
+            .strip_prefix(EXTRA_COLUMN_PREFIX)
+            .and_then(|name| obj.extra.get(name).cloned())
+            .unwrap_or_default(),
+    }
+}
+
+/// Display the records of a focused scan, escaping control characters unless `raw`
+pub fn display_result_set<T: Record>(set: &ResultSet<T>, raw: bool) {
+    print!("{}", format_result_set(set, raw));
+}
+
+/// Prompt user for export options and export the records of a focused scan
+pub fn prompt_result_set_export<T: Record>(
+    set: &ResultSet<T>,
+    options: &ExportOptions,
+) -> Result<()> {
+    let (format, path) = match prompt_export_target(RECORD_EXPORT_FORMATS)? {
+        Some(target) => target,
+        None => return Ok(()),
+    };
+    export_result_set(set, &format, &path, options);
+    Ok(())
+}
+
+/// Export the records of a focused scan to the given path in the `--format` format,
+/// reporting the outcome
+pub fn export_result_set_to_path<T: Record>(
+    set: &ResultSet<T>,
+    path: &str,
+    options: &ExportOptions,
+) {
+    if !RECORD_EXPORT_FORMATS.contains(&options.format.as_str()) {
+        println!(
+            "Format {} is not available here; use one of {}",
+            options.format,
+            RECORD_EXPORT_FORMATS.join(", ")
+        );
+        return;
+    }
+    export_result_set(set, &options.format, path, options);
+}
+
+/// Write the records in one of the [`RECORD_EXPORT_FORMATS`] and report the outcome
+fn export_result_set<T: Record>(
+    set: &ResultSet<T>,
+    format: &str,
+    path: &str,
+    options: &ExportOptions,
+) {
+    let export_result = interrupt::guard_write(|| {
+        let file = if matches!(format, "txt" | "csv" | "tsv") {
+            create_text_file(path, options)?
+        } else {
+            File::create(path)?
+        };
+        let mut writer = BufWriter::new(file);
+        match format {
+            "txt" => writer.write_all(format_result_set(set, options.raw_values).as_bytes())?,
+            "csv" | "tsv" => {
+                let delimiter = if format == "csv" { b',' } else { b'\t' };
+                let mut wtr = WriterBuilder::new().delimiter(delimiter).from_writer(&mut writer);
+                wtr.write_record(T::COLUMNS)?;
+                for record in &set.records {
+                    let values = record.values().into_iter().map(|value| {
+                        let value = value.unwrap_or_default();
+                        escape_controls(&value, options.raw_values).into_owned()
+                    });
+                    wtr.write_record(values)?;
+                }
+                wtr.flush()?;
+            }
+            "json" => {
+                if options.json_compact {
+                    serde_json::to_writer(&mut writer, &set.records)?;
+                } else {
+                    serde_json::to_writer_pretty(&mut writer, &set.records)?;
+                }
+                writer.write_all(b"\n")?;
+            }
+            _ => {
+                for record in &set.records {
+                    serde_json::to_writer(&mut writer, record)?;
+                    writer.write_all(b"\n")?;
+                }
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    });
+    report_export_result(export_result, path, format, options.open);
+}
+
+/// Render the records as the text shown on the console and written to TXT exports: the first
+/// column heads each record, the other columns follow indented and are omitted when unset.
+/// Values have their control characters escaped unless `raw`.
+fn format_result_set<T: Record>(set: &ResultSet<T>, raw: bool) -> String {
+    let mut output = String::new();
+    output.push_str(&format!("=== {} ===\n", set.title));
+    output.push_str(&format!(
+        "Scanned {} keys, found {}\n\n",
+        set.stats.enumerated,
+        set.records.len()
+    ));
+
+    for record in &set.records {
+        for (i, (column, value)) in T::COLUMNS.iter().zip(record.values()).enumerate() {
+            match value.as_deref().map(|value| escape_controls(value, raw)) {
+                Some(value) if i == 0 => output.push_str(&format!("{column}: {value}\n")),
+                Some(value) => output.push_str(&format!("  {column}: {value}\n")),
+                None => {}
+            }
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Display every registered version of a type library, escaping control characters unless `raw`
+pub fn display_typelib_versions(guid: &str, versions: &[TypeLibVersion], raw: bool) {
+    print!("{}", format_typelib_versions(guid, versions, raw));
+}
+
+/// Prompt user for export options and export the type library versions
+pub fn prompt_typelib_export(
+    guid: &str,
+    versions: &[TypeLibVersion],
+    options: &ExportOptions,
+) -> Result<()> {
+    let (format, path) = match prompt_export_target(&["txt", "csv"])? {
+        Some(target) => target,
+        None => return Ok(()),
+    };
+
+    let export_result = interrupt::guard_write(|| {
+        if format == "txt" {
+            let text = format_typelib_versions(guid, versions, options.raw_values);
+            create_text_file(&path, options)
+                .and_then(|mut file| file.write_all(text.as_bytes()))
+                .map_err(Into::into)
+        } else {
+            export_typelib_csv(versions, &path, options)
+        }
+    });
+
+    report_export_result(export_result, &path, &format, options.open);
+    Ok(())
+}
+
+/// Render the type library versions as the text shown on the console and written to TXT
+/// exports, with control characters in the registry values escaped unless `raw`
+fn format_typelib_versions(guid: &str, versions: &[TypeLibVersion], raw: bool) -> String {
+    let escape = |text: &str| escape_controls(text, raw).into_owned();
+    let mut output = String::new();
+    output.push_str("=== TypeLib Versions ===\n");
+    output.push_str(&format!("TypeLib: {}\n", escape(guid)));
+    output.push_str(&format!("Registered versions: {}\n\n", versions.len()));
+
+    for version in versions {
+        output.push_str(&format!("Version: {}\n", escape(&version.version)));
+        if let Some(ref desc) = version.description {
+            output.push_str(&format!("  Description: {}\n", escape(desc)));
+        }
+        if let Some(ref flags) = version.flags {
+            output.push_str(&format!("  Flags: {}\n", escape(flags)));
+        }
+        for platform in &version.platforms {
+            output.push_str(&format!(
+                "  LCID {} / {}: {}\n",
+                escape(&platform.lcid),
+                escape(&platform.platform),
+                escape(platform.path.as_deref().unwrap_or("(no path)"))
+            ));
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Print a `--dump` of a CLSID key as an indented tree of keys and their values, with control
+/// characters in key and value names escaped unless `raw`
+pub fn display_key_dump(view_name: &str, dump: &KeyDump, raw: bool) {
+    println!("=== Raw Registry Dump ({view_name}) ===");
+    print!("{}", format_key_dump(dump, 0, raw));
+    println!();
+}
+
+/// One `[key]` line per key and one `name (type) = data` line per value, indented by depth
+fn format_key_dump(dump: &KeyDump, depth: usize, raw: bool) -> String {
+    let indent = "  ".repeat(depth);
+    let mut output = format!("{indent}[{}]\n", escape_controls(&dump.name, raw));
+    if !dump.readable {
+        output.push_str(&format!("{indent}  (could not be opened)\n"));
+    }
+    for value in &dump.values {
+        let name = if value.name.is_empty() { "(Default)" } else { value.name.as_str() };
+        let name = escape_controls(name, raw);
+        output.push_str(&format!(
+            "{indent}  {name} ({}) = {}\n",
+            value.type_name(),
+            value.data_text()
+        ));
+    }
+    for subkey in &dump.subkeys {
+        output.push_str(&format_key_dump(subkey, depth + 1, raw));
+    }
+    output
+}
+
+/// Export type library versions to a CSV file, one row per platform registration
+fn export_typelib_csv(
+    versions: &[TypeLibVersion],
+    path: &str,
+    options: &ExportOptions,
+) -> Result<()> {
+    let mut wtr = Writer::from_writer(create_text_file(path, options)?);
+    wtr.write_record(["Version", "Description", "Flags", "LCID", "Platform", "Path"])?;
+
+    let escape = |text: &str| escape_controls(text, options.raw_values).into_owned();
+    for version in versions {
+        let version_name = escape(&version.version);
+        let description = escape(version.description.as_deref().unwrap_or(""));
+        let flags = escape(version.flags.as_deref().unwrap_or(""));
+        if version.platforms.is_empty() {
+            wtr.write_record([&version_name, &description, &flags, "", "", ""])?;
+        }
+        for platform in &version.platforms {
+            wtr.write_record([
+                &version_name,
+                &description,
+                &flags,
+                &escape(&platform.lcid),
+                &escape(&platform.platform),
+                &escape(platform.path.as_deref().unwrap_or("")),
+            ])?;
+        }
     }
 
     wtr.flush()?;
     Ok(())
 }
 
-/// Assess programmatic usability of a COM object
-pub fn check_usability(obj: &ComObject) -> &'static str {
-    // An object is more likely to be programmatically usable if:
-    // 1. It has a ProgID (can be instantiated by name)
-    // 2. It has a description (indicates it's documented)
-    match (&obj.prog_id, &obj.description) {
-        (Some(_), Some(_)) => "✓ High (has ProgID and description)",
-        (Some(_), None) => "~ Medium (has ProgID)",
-        (None, Some(_)) => "~ Low (no ProgID, has description)",
-        (None, None) => "✗ Very Low (no ProgID or description)",
+/// Assess programmatic usability of a COM object with the given scorer, usually
+/// `&DefaultScorer` or the `--usability-weights` scorer of the export options
+pub fn check_usability(obj: &ComObject, usability: &dyn UsabilityScorer) -> &'static str {
+    usability.label(usability.score(obj).level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compare::FieldChange;
+    use crate::types::RegistryRoot;
+    use clap::Parser;
+
+    /// Objects that agree on every sort key, so only the CLSID tiebreak orders them
+    fn tied_objects() -> HashMap<String, ComObject> {
+        let clsids = [
+            "{C0000000-0000-0000-0000-000000000003}",
+            "{A0000000-0000-0000-0000-000000000001}",
+            "{B0000000-0000-0000-0000-000000000002}",
+        ];
+        clsids
+            .into_iter()
+            .map(|clsid| {
+                let mut obj = ComObject::new(clsid.to_string(), RegistryRoot::ClassesRoot);
+                obj.prog_id = Some("Same.ProgID".to_string());
+                obj.description = Some("Same description".to_string());
+                obj.suspicion_score = 10;
+                (obj.clsid.clone(), obj)
+            })
+            .collect()
+    }
+
+    fn sorted_clsids(objects: &HashMap<String, ComObject>, args: &[&str]) -> Vec<String> {
+        let args = Args::parse_from(["rust-ole-inspector"].iter().chain(args));
+        let options = ExportOptions::from_args(&args);
+        sort_objects(objects, &options).iter().map(|obj| obj.clsid.clone()).collect()
+    }
+
+    #[test]
+    fn ties_are_broken_by_clsid_for_every_sort_key() {
+        let objects = tied_objects();
+        let ascending = vec![
+            "{A0000000-0000-0000-0000-000000000001}".to_string(),
+            "{B0000000-0000-0000-0000-000000000002}".to_string(),
+            "{C0000000-0000-0000-0000-000000000003}".to_string(),
+        ];
+        let descending: Vec<String> = ascending.iter().rev().cloned().collect();
+
+        for key in SortKey::value_variants() {
+            let name = key.to_possible_value().unwrap().get_name().to_string();
+            assert_eq!(sorted_clsids(&objects, &["--sort", &name]), ascending, "--sort {name}");
+            assert_eq!(
+                sorted_clsids(&objects, &["--sort", &name, "--sort-desc"]),
+                descending,
+                "--sort {name} --sort-desc"
+            );
+        }
+    }
+
+    #[test]
+    fn sorting_is_repeatable() {
+        let objects = tied_objects();
+        let first = sorted_clsids(&objects, &["--sort", "usability"]);
+        for _ in 0..10 {
+            assert_eq!(sorted_clsids(&tied_objects(), &["--sort", "usability"]), first);
+        }
+    }
+
+    fn deviations() -> Vec<Deviation> {
+        let deviation = |status, clsid: &str, prog_id: &str, changes| Deviation {
+            status,
+            clsid: clsid.to_string(),
+            prog_id: Some(prog_id.to_string()),
+            changes,
+        };
+        let change = FieldChange {
+            field: "InprocServer32",
+            old: Some(r"C:\Windows\old.dll".to_string()),
+            new: None,
+        };
+        vec![
+            deviation(DeviationStatus::Unexpected, "{A}", "New.Object", Vec::new()),
+            deviation(DeviationStatus::Removed, "{B}", "Gone.Object", Vec::new()),
+            deviation(DeviationStatus::Changed, "{C}", "Moved.Object", vec![change]),
+        ]
+    }
+
+    #[test]
+    fn baseline_report_lists_every_status_and_change() {
+        let report = format_baseline_deviations("baseline.json", 2, &deviations(), false, false);
+        assert!(report.starts_with("--- Baseline Comparison (baseline.json, 2 objects) ---\n"));
+        assert!(report.contains("UNEXPECTED (1):\n  {A} New.Object\n"));
+        assert!(report.contains("REMOVED (1):\n  {B} Gone.Object\n"));
+        let changed = concat!(
+            "CHANGED (1):\n  {C} Moved.Object\n",
+            "    InprocServer32: \"C:\\\\Windows\\\\old.dll\" → (not set)\n",
+        );
+        assert!(report.contains(changed));
+        assert!(!report.contains('\x1b'));
+    }
+
+    #[test]
+    fn baseline_report_colors_each_status() {
+        let report = format_baseline_deviations("baseline.json", 2, &deviations(), true, false);
+        let green = "\x1b[32mUNEXPECTED (1):\x1b[0m\n\x1b[32m  {A} New.Object\x1b[0m\n";
+        let red = "\x1b[31mREMOVED (1):\x1b[0m\n\x1b[31m  {B} Gone.Object\x1b[0m\n";
+        assert!(report.contains(green));
+        assert!(report.contains(red));
+        assert!(report.contains("\x1b[33mCHANGED (1):\x1b[0m"));
+        assert!(report.contains("    InprocServer32: \x1b[33m\""));
+    }
+
+    #[test]
+    fn control_characters_are_escaped_unless_raw() {
+        let text = "Evil\nObject\x1b[2J\t";
+        assert_eq!(escape_controls(text, false), "Evil\\nObject\\x1b[2J\\t");
+        assert_eq!(escape_controls(text, true), text);
+        assert!(matches!(escape_controls("Plain text", false), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn typelib_text_escapes_registry_values() {
+        let versions = [TypeLibVersion {
+            version: "1.0".to_string(),
+            description: Some("Line one\nLine two".to_string()),
+            flags: None,
+            platforms: Vec::new(),
+        }];
+        let text = format_typelib_versions("{GUID}", &versions, false);
+        assert!(text.contains("  Description: Line one\\nLine two\n"));
+        let raw = format_typelib_versions("{GUID}", &versions, true);
+        assert!(raw.contains("  Description: Line one\nLine two\n"));
     }
 }
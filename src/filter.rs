@@ -1,70 +1,205 @@
 //! Filter logic for COM objects.
-//! 
+//!
 //! This module contains all filtering and matching logic used during registry scanning.
-//! It supports multiple filter types: interactive, description-based, CLSID-based, and app-based.
+//! It supports multiple filter types: interactive, description-based, CLSID-based, ProgID-based,
+//! and app-based.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::types::{Args, ComObject, FilterLogic, RegistrationScope, UsabilityLevel};
+use crate::usability::{DefaultScorer, UsabilityScorer};
+
+/// Built-in application keyword sets for `--preset`. Each preset expands into `--filter-app`
+/// keywords; add a row here to add a preset.
+pub const FILTER_PRESETS: &[(&str, &[&str])] = &[
+    ("office", &["excel", "word", "powerpoint", "outlook", "access"]),
+    ("media", &["wmp", "directshow", "mediafoundation"]),
+    ("shell", &["shellext", "contextmenu"]),
+];
+
+/// Validates a `--preset` name against [`FILTER_PRESETS`]
+pub fn parse_preset(name: &str) -> Result<String, String> {
+    let name = name.to_lowercase();
+    if FILTER_PRESETS.iter().any(|(preset, _)| *preset == name) {
+        Ok(name)
+    } else {
+        let names: Vec<&str> = FILTER_PRESETS.iter().map(|(preset, _)| *preset).collect();
+        Err(format!("unknown preset (possible values: {})", names.join(", ")))
+    }
+}
+
+/// Parses a `--modified-since` date: RFC 3339 with an offset, or a date or date-time
+/// without one, taken as UTC
+pub fn parse_since(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime.with_timezone(&Utc));
+    }
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S") {
+        return Ok(datetime.and_utc());
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_time(Default::default()).and_utc())
+        .map_err(|_| "expected an ISO 8601 date such as 2024-05-01 or 2024-05-01T12:00:00Z".into())
+}
+
+/// The `--recent` cutoff: the current time minus `days` days
+fn recent_cutoff(days: u32) -> DateTime<Utc> {
+    DateTime::<Utc>::from(SystemTime::now()) - TimeDelta::days(i64::from(days))
+}
+
+/// The set of active filters applied to every scanned COM object
+#[derive(Debug, Clone, Default)]
+pub struct FilterCriteria {
+    pub interactive: Option<String>,
+    /// `--filter`: searches ProgID, description, and CLSID
+    pub text: Option<String>,
+    pub description: Option<String>,
+    pub clsid: Option<String>,
+    /// `--filter-progid`: matches the ProgID only
+    pub prog_id: Option<String>,
+    /// `--filter-appid`: exact AppID GUID
+    pub app_id: Option<String>,
+    pub app: Option<Vec<String>>,
+    /// How `text`, `description` and `clsid` combine with each other
+    pub logic: FilterLogic,
+    pub only_scriptable: bool,
+    pub only_controls: bool,
+    pub only_insertable: bool,
+    pub only_unquoted: bool,
+    pub only_typelib_no_progid: bool,
+    pub only_user_scope: bool,
+    pub min_usability: Option<UsabilityLevel>,
+    /// Rates objects for `min_usability`; `None` uses [`DefaultScorer`]
+    pub usability: Option<Arc<dyn UsabilityScorer>>,
+    /// `--only-shell-ext`: CLSIDs of every registered shell extension handler in
+    /// [`normalize_clsid`] form, read once before the scan
+    pub shell_ext_clsids: Option<HashSet<String>>,
+    /// `--modified-since`, or the cutoff of `--recent`: the CLSID key must have been written
+    /// after this time
+    pub modified_since: Option<DateTime<Utc>>,
+    /// Minimum similarity score (0-100) for fuzzy matching; `None` means exact substring matching
+    pub fuzzy_threshold: Option<u8>,
+    /// Compare filter terms without lowercasing either side
+    pub case_sensitive: bool,
+}
+
+impl FilterCriteria {
+    /// Build the filter criteria from the command-line arguments and the interactive prompt
+    pub fn from_args(args: &Args, interactive: Option<String>) -> Self {
+        Self {
+            interactive,
+            text: args.filter.clone(),
+            description: args.filter_description.clone(),
+            clsid: args.filter_clsid.clone(),
+            prog_id: args.filter_progid.clone(),
+            app_id: args.filter_appid.clone(),
+            app: app_keywords(args),
+            logic: args.filter_logic,
+            only_scriptable: args.only_scriptable,
+            only_controls: args.only_controls,
+            only_insertable: args.only_insertable,
+            only_unquoted: args.only_unquoted,
+            only_typelib_no_progid: args.only_typelib_no_progid,
+            only_user_scope: args.only_user_scope,
+            min_usability: args.min_usability,
+            usability: None,
+            shell_ext_clsids: None,
+            modified_since: args.modified_since.or_else(|| args.recent.map(recent_cutoff)),
+            fuzzy_threshold: args.fuzzy.then_some(args.fuzzy_threshold),
+            case_sensitive: args.case_sensitive,
+        }
+    }
+}
+
+/// Union of the `--filter-app` keywords and the keywords of every selected preset
+fn app_keywords(args: &Args) -> Option<Vec<String>> {
+    let mut keywords = args.filter_app.clone().unwrap_or_default();
+    for name in args.preset.iter().flatten() {
+        let presets = FILTER_PRESETS.iter().filter(|(preset, _)| preset == name);
+        for keyword in presets.flat_map(|(_, keywords)| keywords.iter()) {
+            if !keywords.iter().any(|k| k.eq_ignore_ascii_case(keyword)) {
+                keywords.push(keyword.to_string());
+            }
+        }
+    }
+
+    if keywords.is_empty() {
+        None
+    } else {
+        Some(keywords)
+    }
+}
 
 /// Determines if a COM object should be included based on all active filters
-pub fn should_include_object(
-    prog_id: &Option<String>,
-    description: &Option<String>,
-    clsid: &str,
-    interactive_filter: &Option<String>,
-    filter_description: &Option<String>,
-    filter_clsid: &Option<String>,
-    filter_app: &Option<Vec<String>>,
-) -> bool {
+pub fn should_include_object(obj: &ComObject, criteria: &FilterCriteria) -> bool {
+    let prog_id = obj.prog_id.as_deref();
+    let description = obj.description.as_deref();
+    let clsid = obj.clsid.as_str();
+
     // Check interactive filter (searches ProgID, description, and CLSID)
-    if let Some(ref filter) = interactive_filter {
-        let filter_lower = filter.to_lowercase();
-        let matches = prog_id
-            .as_ref()
-            .map(|p| p.to_lowercase().contains(&filter_lower))
-            .unwrap_or(false)
-            || description
-                .as_ref()
-                .map(|d| d.to_lowercase().contains(&filter_lower))
-                .unwrap_or(false)
-            || clsid.to_lowercase().contains(&filter_lower);
+    if let Some(ref filter) = criteria.interactive {
+        let matches = text_matches(prog_id, filter, criteria)
+            || text_matches(description, filter, criteria)
+            || clsid_contains(clsid, filter);
 
         if !matches {
             return false;
         }
     }
 
-    // Check description filter
-    if let Some(ref desc_filter) = filter_description {
-        let desc_filter_lower = desc_filter.to_lowercase();
-        let desc_matches = description
-            .as_ref()
-            .map(|d| d.to_lowercase().contains(&desc_filter_lower))
-            .unwrap_or(false);
+    // Check the text, description and CLSID filters, combined with AND or OR
+    let mut results = Vec::new();
+    if let Some(ref text_filter) = criteria.text {
+        results.push(
+            text_matches(prog_id, text_filter, criteria)
+                || text_matches(description, text_filter, criteria)
+                || clsid_contains(clsid, text_filter),
+        );
+    }
+    if let Some(ref desc_filter) = criteria.description {
+        results.push(text_matches(description, desc_filter, criteria));
+    }
+    if let Some(ref clsid_filter) = criteria.clsid {
+        results.push(clsid_contains(clsid, clsid_filter));
+    }
+    if !results.is_empty() {
+        let matches = match criteria.logic {
+            FilterLogic::And => results.iter().all(|&r| r),
+            FilterLogic::Or => results.iter().any(|&r| r),
+        };
+        if !matches {
+            return false;
+        }
+    }
 
-        if !desc_matches {
+    // Check ProgID filter
+    if let Some(ref progid_filter) = criteria.prog_id {
+        if !text_matches(prog_id, progid_filter, criteria) {
             return false;
         }
     }
 
-    // Check CLSID filter
-    if let Some(ref clsid_filter) = filter_clsid {
-        let clsid_filter_lower = clsid_filter.to_lowercase();
-        if !clsid.to_lowercase().contains(&clsid_filter_lower) {
+    // Check AppID filter (objects without an AppID never match)
+    if let Some(ref appid_filter) = criteria.app_id {
+        let matches = obj
+            .app_id
+            .as_deref()
+            .is_some_and(|app_id| same_guid(app_id, appid_filter));
+
+        if !matches {
             return false;
         }
     }
 
     // Check app filter (comma-separated keywords)
-    if let Some(ref app_filters) = filter_app {
+    if let Some(ref app_filters) = criteria.app {
         let matches = app_filters.iter().any(|app| {
-            let app_lower = app.to_lowercase();
-            prog_id
-                .as_ref()
-                .map(|p| p.to_lowercase().contains(&app_lower))
-                .unwrap_or(false)
-                || description
-                    .as_ref()
-                    .map(|d| d.to_lowercase().contains(&app_lower))
-                    .unwrap_or(false)
-                || clsid.to_lowercase().contains(&app_lower)
+            text_matches(prog_id, app, criteria)
+                || text_matches(description, app, criteria)
+                || clsid_contains(clsid, app)
         });
 
         if !matches {
@@ -72,5 +207,138 @@ pub fn should_include_object(
         }
     }
 
+    // Check safe-for-scripting flag
+    if criteria.only_scriptable && !obj.safe_for_scripting {
+        return false;
+    }
+
+    // Check ActiveX control flag
+    if criteria.only_controls && !obj.is_control {
+        return false;
+    }
+
+    // Check insertable (OLE document) flag
+    if criteria.only_insertable && !obj.is_insertable {
+        return false;
+    }
+
+    // Check unquoted LocalServer32 path
+    if criteria.only_unquoted && !obj.unquoted_path {
+        return false;
+    }
+
+    // Check TypeLib without ProgID (early binding only)
+    if criteria.only_typelib_no_progid && !obj.has_typelib_without_prog_id() {
+        return false;
+    }
+
+    // Check shell extension registration
+    if let Some(ref clsids) = criteria.shell_ext_clsids {
+        if !clsids.contains(&normalize_clsid(&obj.clsid)) {
+            return false;
+        }
+    }
+
+    // Check per-user registration scope
+    if criteria.only_user_scope
+        && !matches!(
+            obj.scope,
+            Some(RegistrationScope::User) | Some(RegistrationScope::Both)
+        )
+    {
+        return false;
+    }
+
+    // Check minimum usability level
+    if let Some(min_usability) = criteria.min_usability {
+        let scorer = criteria.usability.as_deref().unwrap_or(&DefaultScorer);
+        if scorer.score(obj).level < min_usability {
+            return false;
+        }
+    }
+
+    // Check last write time (objects without a timestamp never match)
+    if let Some(since) = criteria.modified_since {
+        if obj.last_write_time.is_none_or(|written| written <= since) {
+            return false;
+        }
+    }
+
     true
 }
+
+/// Matches the `--filter-signer` text against an object's signer (case-insensitive).
+/// A leading `!` inverts the match, so `!Microsoft` keeps unsigned objects and objects signed
+/// by anyone else.
+pub fn signer_matches(obj: &ComObject, filter: &str) -> bool {
+    let (negate, needle) = match filter.strip_prefix('!') {
+        Some(needle) => (true, needle),
+        None => (false, filter),
+    };
+    let matches = obj
+        .signer
+        .as_deref()
+        .is_some_and(|signer| contains(signer, needle, false));
+    matches != negate
+}
+
+/// Matches a ProgID/description field against a filter term, either by substring
+/// or, in fuzzy mode, by the best token similarity score
+fn text_matches(field: Option<&str>, needle: &str, criteria: &FilterCriteria) -> bool {
+    let Some(field) = field else {
+        return false;
+    };
+
+    if contains(field, needle, criteria.case_sensitive) {
+        return true;
+    }
+
+    match criteria.fuzzy_threshold {
+        Some(threshold) => fuzzy_score(field, needle, criteria.case_sensitive) >= threshold,
+        None => false,
+    }
+}
+
+/// The form every CLSID comparison uses: surrounding whitespace and braces stripped, lower
+/// case. Registry key names carry braces, but GUIDs are often pasted without them, and GUIDs
+/// are case-insensitive, so `{0002DF01-...}` and `0002df01-...` compare equal.
+pub fn normalize_clsid(clsid: &str) -> String {
+    clsid.trim().trim_start_matches('{').trim_end_matches('}').to_lowercase()
+}
+
+/// CLSIDs are always matched by substring of the normalized forms, since similarity scores
+/// are meaningless for GUIDs; `--case-sensitive` does not apply to them
+fn clsid_contains(clsid: &str, needle: &str) -> bool {
+    normalize_clsid(clsid).contains(&normalize_clsid(needle))
+}
+
+/// Exact GUID comparison ignoring surrounding braces and case
+pub fn same_guid(a: &str, b: &str) -> bool {
+    normalize_clsid(a) == normalize_clsid(b)
+}
+
+/// Substring check that ignores case unless case-sensitive matching was requested
+fn contains(haystack: &str, needle: &str, case_sensitive: bool) -> bool {
+    if case_sensitive {
+        haystack.contains(needle)
+    } else {
+        haystack.to_lowercase().contains(&needle.to_lowercase())
+    }
+}
+
+/// Best Jaro-Winkler similarity (0-100) between the needle and any alphanumeric token of the text
+fn fuzzy_score(text: &str, needle: &str, case_sensitive: bool) -> u8 {
+    let (text, needle) = if case_sensitive {
+        (text.to_string(), needle.to_string())
+    } else {
+        (text.to_lowercase(), needle.to_lowercase())
+    };
+
+    let best = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| strsim::jaro_winkler(token, &needle))
+        .fold(0.0_f64, f64::max);
+
+    (best * 100.0).round() as u8
+}
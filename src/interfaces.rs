@@ -0,0 +1,69 @@
+//! Interface identifier (IID) names.
+//!
+//! Raw IIDs are unreadable in listings, so they are resolved to names: first from the
+//! `HKEY_CLASSES_ROOT\Interface\{iid}` registration, then from a built-in table of the most
+//! common system interfaces, which also covers IIDs that are not registered.
+
+use std::collections::HashMap;
+
+use crate::registry::read_interface_name;
+
+/// Well-known system interfaces, the fallback for IIDs without a registered name
+pub const WELL_KNOWN_INTERFACES: &[(&str, &str)] = &[
+    ("{00000000-0000-0000-C000-000000000046}", "IUnknown"),
+    ("{00000001-0000-0000-C000-000000000046}", "IClassFactory"),
+    ("{00000003-0000-0000-C000-000000000046}", "IMarshal"),
+    ("{0000000B-0000-0000-C000-000000000046}", "IStorage"),
+    ("{0000000C-0000-0000-C000-000000000046}", "IStream"),
+    ("{00000109-0000-0000-C000-000000000046}", "IPersistStream"),
+    ("{0000010B-0000-0000-C000-000000000046}", "IPersistFile"),
+    ("{0000010C-0000-0000-C000-000000000046}", "IPersist"),
+    ("{0000010E-0000-0000-C000-000000000046}", "IDataObject"),
+    ("{00000112-0000-0000-C000-000000000046}", "IOleObject"),
+    ("{00020400-0000-0000-C000-000000000046}", "IDispatch"),
+    ("{00020401-0000-0000-C000-000000000046}", "ITypeInfo"),
+    ("{00020402-0000-0000-C000-000000000046}", "ITypeLib"),
+    ("{00020404-0000-0000-C000-000000000046}", "IEnumVARIANT"),
+    ("{000214E4-0000-0000-C000-000000000046}", "IContextMenu"),
+    ("{000214E8-0000-0000-C000-000000000046}", "IShellExtInit"),
+    ("{000214E9-0000-0000-C000-000000000046}", "IShellPropSheetExt"),
+    ("{000214FA-0000-0000-C000-000000000046}", "IExtractIconW"),
+    ("{00021500-0000-0000-C000-000000000046}", "IQueryInfo"),
+    ("{B196B283-BAB4-101A-B69C-00AA00341D07}", "IProvideClassInfo"),
+    ("{B196B284-BAB4-101A-B69C-00AA00341D07}", "IConnectionPointContainer"),
+    ("{B196B286-BAB4-101A-B69C-00AA00341D07}", "IConnectionPoint"),
+    ("{CB5BDC81-93C1-11CF-8F20-00805F2CD064}", "IObjectSafety"),
+    ("{FC4801A3-2BA9-11CF-A229-00AA003D7352}", "IObjectWithSite"),
+];
+
+/// Looks up the name of a well-known interface (case-insensitive)
+pub fn well_known_interface_name(iid: &str) -> Option<&'static str> {
+    WELL_KNOWN_INTERFACES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(iid))
+        .map(|(_, name)| *name)
+}
+
+/// Resolves IIDs to interface names, remembering every answer so each IID is looked up in
+/// the registry only once
+#[derive(Debug, Default)]
+pub struct IidResolver {
+    names: HashMap<String, Option<String>>,
+}
+
+impl IidResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The registered name of the interface, else its well-known name
+    pub fn iid_to_name(&mut self, iid: &str) -> Option<String> {
+        self.names
+            .entry(iid.to_uppercase())
+            .or_insert_with(|| {
+                read_interface_name(iid)
+                    .or_else(|| well_known_interface_name(iid).map(str::to_string))
+            })
+            .clone()
+    }
+}
@@ -0,0 +1,65 @@
+//! Ctrl+C handling shared by the scan and the interactive prompts.
+//!
+//! A single handler sets a shutdown flag. The scan loop polls it to stop early with partial
+//! results, and the stdin prompts check it after every read so they can exit cleanly.
+
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set by the Ctrl+C handler
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Set while an export file is being written, so a repeated Ctrl+C cannot cut it off half-written
+static WRITING: AtomicBool = AtomicBool::new(false);
+
+/// Install the Ctrl+C handler. The first Ctrl+C only sets the shutdown flag;
+/// a second one exits immediately unless an export is being written.
+pub fn install_handler() {
+    let result = ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::Relaxed) && !WRITING.load(Ordering::Relaxed) {
+            std::process::exit(130);
+        }
+    });
+    if let Err(e) = result {
+        eprintln!("Warning: could not install Ctrl+C handler: {e}");
+    }
+}
+
+/// The shutdown flag, for code that polls it directly (such as the scan loop)
+pub fn flag() -> &'static AtomicBool {
+    &INTERRUPTED
+}
+
+/// Returns true once Ctrl+C has been pressed
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+/// Reset the shutdown flag after a cancelled scan, so the prompts react to the next Ctrl+C
+pub fn clear() {
+    INTERRUPTED.store(false, Ordering::Relaxed);
+}
+
+/// Read one trimmed line from stdin.
+/// Returns `None` if Ctrl+C was pressed (before or during the read) or stdin reached end-of-file.
+pub fn read_line() -> io::Result<Option<String>> {
+    if is_interrupted() {
+        return Ok(None);
+    }
+
+    let mut input = String::new();
+    let read = io::stdin().read_line(&mut input)?;
+    if read == 0 || is_interrupted() {
+        return Ok(None);
+    }
+    Ok(Some(input.trim().to_string()))
+}
+
+/// Run an export write while deferring the forced exit of a repeated Ctrl+C,
+/// so the file is completely written and flushed before the program can stop
+pub fn guard_write<T>(write: impl FnOnce() -> T) -> T {
+    WRITING.store(true, Ordering::Relaxed);
+    let result = write();
+    WRITING.store(false, Ordering::Relaxed);
+    result
+}
@@ -1,24 +1,96 @@
+mod acl;
+mod analysis;
+mod cache;
+mod categories;
+mod classes;
+mod compare;
+mod clipboard;
 mod console;
 mod display;
 mod filter;
+mod interfaces;
+mod interrupt;
+mod modules;
+mod probe;
 mod registry;
+mod sample;
 mod security;
+mod security_scan;
+mod server_path;
+mod shell;
+mod shell_ext;
+mod signature;
 mod types;
+mod usability;
+mod version_info;
 
 use anyhow::Result;
 use clap::Parser;
-use std::collections::HashMap;
-use std::io;
-use windows::Win32::System::Registry::{KEY_WOW64_32KEY, KEY_WOW64_64KEY};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use windows::Win32::System::Registry::{KEY_WOW64_32KEY, KEY_WOW64_64KEY, REG_SAM_FLAGS};
 
-use console::{init_console_utf8, print_header_art_ascii, print_header_art_unicode};
-use display::{display_results, prompt_export};
-use registry::scan_com_objects;
+use console::{
+    enable_ansi_colors, init_console_utf8, print_header_art_ascii, print_header_art_unicode,
+};
+use display::{
+    apply_limit, copy_export_to_clipboard, display_key_dump, display_result_set, display_results,
+    display_typelib_versions, escape_controls, export_result_set_to_path, export_stats_json,
+    export_to_path, export_vendor_groups,
+    prompt_export, prompt_result_set_export, prompt_typelib_export, report_baseline_deviations,
+    report_duplicate_prog_ids, report_inaccessible_keys, report_multi_prog_ids,
+    report_vendor_groups, report_view_conflicts, ExportOptions,
+};
+use filter::{normalize_clsid, same_guid, should_include_object, signer_matches, FilterCriteria};
+use registry::{
+    check_key_acls, dump_clsid_key, lookup_com_object, read_browser_helper_objects,
+    read_extra_values, read_prog_id_index, read_registration_scopes, read_shell_extensions,
+    read_typelib_versions, resolve_treat_as, scan_com_objects, scan_com_objects_with,
+    scan_com_objects_with_progress, scan_interfaces, ScanOptions,
+};
 use security::check_privileges;
-use types::{Args, ComObject};
+use usability::{LegacyScorer, WeightedScorer};
+use types::{
+    Args, ColorChoice, ComObject, InterfaceInfo, ResultSet, ScanStats, ShellExtension,
+    VendorSource, ViewSelection,
+};
 
-fn main() -> Result<()> {
+/// Frames of the indeterminate `--spinner`, advanced on every progress update
+const SPINNER_FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+
+/// Process exit codes, listed in the README. A runtime error exits with [`EXIT_ERROR`].
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_ERROR: i32 = 1;
+/// The scan finished but no object matched the filters
+const EXIT_NO_MATCHES: i32 = 2;
+/// `--baseline` found objects that deviate from the baseline
+const EXIT_DEVIATIONS: i32 = 3;
+/// A `--timeout` cut the scan short, as with the `timeout` command
+const EXIT_TIMED_OUT: i32 = 124;
+
+fn main() {
+    let code = match run() {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            EXIT_ERROR
+        }
+    };
+    // `process::exit` skips destructors, so write out what is still buffered first
+    let _ = std::io::stdout().flush();
+    std::process::exit(code);
+}
+
+/// The whole program; returns the exit code
+fn run() -> Result<i32> {
     let args = Args::parse();
+    let mut export_options = ExportOptions::from_args(&args);
+
+    interrupt::install_handler();
 
     // Set current working directory to the executable's directory
     // This ensures that default file paths are relative to the executable
@@ -28,6 +100,19 @@ fn main() -> Result<()> {
         }
     }
 
+    if let Some(ref path) = args.usability_weights {
+        export_options.usability = Arc::new(WeightedScorer::load(path)?);
+    } else if args.legacy_usability {
+        export_options.usability = Arc::new(LegacyScorer);
+    }
+
+    // Refuse to overwrite an existing `--output` file before spending time on the scan
+    if let Some(ref path) = args.output {
+        if !args.force && Path::new(path).exists() {
+            anyhow::bail!("{path} already exists; pass --force to overwrite it");
+        }
+    }
+
     // Check if running with elevated privileges
     check_privileges();
 
@@ -38,58 +123,268 @@ fn main() -> Result<()> {
     } else {
         print_header_art_ascii();
     }
+    // A console that cannot show colors gets plain text, unless colors were forced
+    if export_options.color && !enable_ansi_colors() && args.color == ColorChoice::Auto {
+        export_options.color = false;
+    }
 
-    // Prompt user for filter
-    println!("Enter a filter for COM objects (leave empty to search all):");
-    let mut interactive_filter = String::new();
-    io::stdin().read_line(&mut interactive_filter)?;
-    let interactive_filter = interactive_filter.trim().to_string();
-    let interactive_filter = if interactive_filter.is_empty() {
-        None
-    } else {
-        Some(interactive_filter)
-    };
+    // Focused TypeLib read: list its versions and skip the CLSID scan entirely
+    if let Some(ref guid) = args.typelib_versions {
+        let code = match read_typelib_versions(guid) {
+            Ok(versions) => {
+                display_typelib_versions(guid, &versions, args.raw_values);
+                if !args.non_interactive {
+                    prompt_typelib_export(guid, &versions, &export_options)?;
+                }
+                if versions.is_empty() {
+                    EXIT_NO_MATCHES
+                } else {
+                    EXIT_SUCCESS
+                }
+            }
+            Err(e) => {
+                eprintln!("Error reading TypeLib {guid}: {e}");
+                EXIT_ERROR
+            }
+        };
+        if !args.non_interactive {
+            wait_for_quit();
+        }
+        return Ok(code);
+    }
 
     // Determine which registry views to scan
+    let selection = ViewSelection::from_flags(args.scan_32bit, args.scan_64bit);
     let mut views_to_scan = Vec::new();
 
-    if args.scan_32bit || !args.scan_64bit {
+    if selection.includes_32bit() {
         views_to_scan.push(("32-bit", KEY_WOW64_32KEY));
     }
 
-    if args.scan_64bit || !args.scan_32bit {
+    if selection.includes_64bit() {
         views_to_scan.push(("64-bit", KEY_WOW64_64KEY));
     }
 
-    let mut all_objects = HashMap::new();
+    // Raw dump: print everything below one CLSID key to see what the scanner reads from it
+    if let Some(ref clsid) = args.dump {
+        let code = dump_clsid(&args, clsid, &views_to_scan);
+        if !args.non_interactive {
+            wait_for_quit();
+        }
+        return Ok(code);
+    }
 
-    for (view_name, view_flag) in views_to_scan {
-        println!("Scanning {view_name} registry view...");
-        match scan_com_objects(
-            view_flag,
-            args.limit,
-            &args.filter_description,
-            &args.filter_clsid,
-            &args.filter_app,
-            &interactive_filter,
-        ) {
-            Ok(objects) => {
-                println!("Found {} COM objects in {} view\n", objects.len(), view_name);
-
-                // Merge objects, preferring those with ProgIDs
-                for (clsid, obj) in objects {
-                    all_objects
-                        .entry(clsid.clone())
-                        .and_modify(|existing: &mut ComObject| {
-                            if obj.prog_id.is_some() && existing.prog_id.is_none() {
-                                existing.prog_id = obj.prog_id.clone();
-                            }
-                            if obj.description.is_some() && existing.description.is_none() {
-                                existing.description = obj.description.clone();
-                            }
-                        })
-                        .or_insert(obj);
+    // Browser Helper Objects: read the CLSIDs listed under the BHO key
+    if args.bho {
+        let clsids = browser_helper_objects(&views_to_scan);
+        println!("Found {} registered Browser Helper Objects\n", clsids.len());
+        return run_lookup(&args, &clsids, &views_to_scan, &export_options, |_| {});
+    }
+
+    // Shell extensions: read the CLSIDs of every registered handler and label each with
+    // its handler types
+    if args.shell_ext {
+        let extensions = shell_extensions(&args, &views_to_scan);
+        let handlers = shell_ext::handlers_by_clsid(&extensions);
+        let mut clsids: Vec<String> =
+            handlers.keys().map(|clsid| format!("{{{}}}", clsid.to_uppercase())).collect();
+        clsids.sort();
+        println!(
+            "Found {} shell extension registrations for {} CLSIDs\n",
+            extensions.len(),
+            clsids.len()
+        );
+        return run_lookup(&args, &clsids, &views_to_scan, &export_options, |objects| {
+            shell_ext::annotate(objects, &handlers)
+        });
+    }
+
+    // Interfaces: enumerate `{root}\Interface` instead of the CLSIDs
+    if args.interfaces {
+        let interfaces = read_interfaces(&args, &views_to_scan);
+        report_inaccessible_keys(&interfaces.stats, &[]);
+        display_result_set(&interfaces, args.raw_values);
+        if let Some(ref path) = args.output {
+            export_result_set_to_path(&interfaces, path, &export_options);
+        } else if !args.non_interactive {
+            prompt_result_set_export(&interfaces, &export_options)?;
+        }
+        if !args.non_interactive {
+            wait_for_quit();
+        }
+        return Ok(if interfaces.records.is_empty() {
+            EXIT_NO_MATCHES
+        } else {
+            EXIT_SUCCESS
+        });
+    }
+
+    // Direct lookup: read just the requested CLSIDs instead of enumerating the hive
+    if let Some(ref clsids) = args.lookup {
+        return run_lookup(&args, clsids, &views_to_scan, &export_options, |_| {});
+    }
+
+    // Benchmark: time repeated scans with the command-line filters, without display or export
+    if args.bench {
+        let mut criteria = FilterCriteria::from_args(&args, None);
+        criteria.usability = Some(export_options.usability.clone());
+        if args.only_shell_ext {
+            let handlers = shell_ext::handlers_by_clsid(&shell_extensions(&args, &views_to_scan));
+            criteria.shell_ext_clsids = Some(handlers.into_keys().collect());
+        }
+        run_benchmark(&args, &views_to_scan, &criteria);
+        return Ok(EXIT_SUCCESS);
+    }
+
+    // Prompt user for filter
+    let unattended = args.non_interactive || args.fingerprint || args.baseline.is_some();
+    let interactive_filter = if unattended {
+        None
+    } else {
+        println!("Enter a filter for COM objects (leave empty to search all):");
+        read_optional_line()?
+    };
+    if interrupt::is_interrupted() {
+        return Ok(EXIT_SUCCESS);
+    }
+    let mut criteria = FilterCriteria::from_args(&args, interactive_filter);
+    criteria.usability = Some(export_options.usability.clone());
+    if let (Some(days), Some(cutoff)) = (args.recent, criteria.modified_since) {
+        let since = cutoff.to_rfc3339();
+        println!("Keeping objects modified in the last {days} days (since {since})\n");
+    }
+    // The shell extension registrations are read once, for the filter and to label the results
+    let shell_handlers = args.only_shell_ext.then(|| {
+        shell_ext::handlers_by_clsid(&shell_extensions(&args, &views_to_scan))
+    });
+    if let Some(ref handlers) = shell_handlers {
+        criteria.shell_ext_clsids = Some(handlers.keys().cloned().collect());
+    }
+    // These filters are applied after the scan, so `--limit` and `--sample` must wait until then
+    let filtered_after_scan = args.only_writable || args.suspicious || args.filter_signer.is_some();
+    // With `--cache`, scan everything and filter afterwards, so the cache holds the full scan
+    let (scan_criteria, scan_limit) = if args.cache.is_some() {
+        (FilterCriteria::default(), 0)
+    } else if filtered_after_scan || args.sample.is_some() {
+        // A limited scan would only sample its first matches
+        (criteria.clone(), 0)
+    } else {
+        (criteria.clone(), args.limit)
+    };
+    let sample_seed = args.sample.map(|_| args.seed.unwrap_or_else(sample::random_seed));
+    // Without later filters the sample is drawn while scanning, holding only the sample
+    let scan_sample = args
+        .sample
+        .filter(|_| args.cache.is_none() && !filtered_after_scan && !args.resolve_classes);
+    if let (Some(size), Some(seed)) = (args.sample, sample_seed) {
+        if !args.quiet {
+            println!("Sampling {size} matching objects with seed {seed}\n");
+        }
+    }
+
+    let mut all_objects: HashMap<String, ComObject> = HashMap::new();
+    let mut scan_stats = ScanStats::default();
+    // Each view's objects before merging, kept only for `--report-view-conflicts`
+    let mut view_results: Vec<(&str, HashMap<String, ComObject>)> = Vec::new();
+    // The CLSIDs each view returned, for the per-view counts of the summary
+    let mut view_clsids: Vec<(&str, HashSet<String>)> = Vec::new();
+    // ProgID and description values each view skipped because access was denied
+    let mut denied_values: Vec<(&str, usize)> = Vec::new();
+
+    let view_names: Vec<&str> = views_to_scan.iter().map(|(name, _)| *name).collect();
+    let cached = load_cache(&args, &view_names);
+    // A usable cache replaces the scan of every view
+    let views_to_read: &[(&str, REG_SAM_FLAGS)] = if cached.is_some() {
+        &[]
+    } else {
+        &views_to_scan
+    };
+    if let Some(cached) = cached {
+        scan_stats = cached.stats;
+        merge_objects(&mut all_objects, cached.objects);
+    }
+
+    let scan_start = Instant::now();
+    let scan_options = ScanOptions {
+        limit: scan_limit,
+        threads: scan_threads(&args),
+        deadline: args.timeout.map(|seconds| scan_start + Duration::from_secs(seconds)),
+    };
+    // The progress line redraws in place on stderr, which only makes sense on an interactive
+    // console; a redirected stderr gets no progress line instead of a stream of `\r` redraws
+    let show_progress = !args.quiet && std::io::stderr().is_terminal();
+    let spinner = args.spinner && show_progress;
+
+    for (view_name, view_flag) in views_to_read {
+        if interrupt::is_interrupted() || scan_options.expired() {
+            break;
+        }
+        if !args.quiet {
+            println!("Scanning {view_name} registry view...");
+        }
+        let view_start = Instant::now();
+        let mut frame = 0;
+        let on_progress = |processed: usize, matched: usize| {
+            if !show_progress {
+                return;
+            }
+            if spinner {
+                eprint!("\r  {} processed {processed}, matched {matched}", SPINNER_FRAMES[frame]);
+                frame = (frame + 1) % SPINNER_FRAMES.len();
+            } else {
+                eprint!("\r  {processed} CLSIDs processed, {matched} matched");
+            }
+        };
+        let result = if args.resolve_classes {
+            classes::scan_resolved(
+                *view_flag,
+                scan_options,
+                &scan_criteria,
+                interrupt::flag(),
+                on_progress,
+            )
+        } else if let (Some(size), Some(seed)) = (scan_sample, sample_seed) {
+            let mut view_sample = sample::Sample::new(size as usize, seed);
+            scan_com_objects_with(
+                args.root,
+                *view_flag,
+                scan_options,
+                &scan_criteria,
+                interrupt::flag(),
+                |obj| view_sample.offer(obj),
+                on_progress,
+            )
+            .map(|stats| (view_sample.into_objects(), stats))
+        } else {
+            scan_com_objects_with_progress(
+                args.root,
+                *view_flag,
+                scan_options,
+                &scan_criteria,
+                interrupt::flag(),
+                on_progress,
+            )
+        };
+        if show_progress {
+            eprintln!();
+        }
+        match result {
+            Ok((objects, stats)) => {
+                if !args.quiet {
+                    println!(
+                        "Found {} COM objects in {} view ({:.2}s)\n",
+                        objects.len(),
+                        view_name,
+                        view_start.elapsed().as_secs_f64()
+                    );
                 }
+                scan_stats.add(&stats);
+                denied_values.push((*view_name, stats.values_denied));
+                view_clsids.push((*view_name, objects.keys().cloned().collect()));
+                if args.report_view_conflicts {
+                    view_results.push((*view_name, objects.clone()));
+                }
+                merge_objects(&mut all_objects, objects.into_values());
             }
             Err(e) => {
                 eprintln!("Error scanning {view_name} view: {e}");
@@ -97,19 +392,674 @@ fn main() -> Result<()> {
         }
     }
 
+    let cancelled = interrupt::is_interrupted();
+    let timed_out = !cancelled && !views_to_read.is_empty() && scan_options.expired();
+    let stopped = if timed_out { "timed out" } else { "cancelled" };
+    // A scan served from the cache took no time
+    let scan_seconds = if views_to_read.is_empty() {
+        0.0
+    } else {
+        scan_start.elapsed().as_secs_f64()
+    };
+    if (cancelled || timed_out) && args.fingerprint {
+        anyhow::bail!("Scan {stopped}; no fingerprint for partial results");
+    } else if (cancelled || timed_out) && args.baseline.is_some() {
+        anyhow::bail!("Scan {stopped}; partial results cannot be compared to the baseline");
+    } else if cancelled {
+        println!("Scan cancelled; showing the partial results collected so far.\n");
+        // The Ctrl+C was consumed by the scan; the prompts below react to the next one
+        interrupt::clear();
+    } else if timed_out {
+        eprintln!(
+            "Warning: scan timed out after {}s; showing the partial results collected so far.\n",
+            args.timeout.unwrap_or_default()
+        );
+    } else if !args.quiet && !views_to_read.is_empty() {
+        println!("Scan completed in {scan_seconds:.2}s\n");
+    }
+    report_inaccessible_keys(&scan_stats, &denied_values);
+    if args.resolve_classes && !args.quiet {
+        let shadowed = all_objects.values().filter(|obj| obj.shadowed).count();
+        println!("{shadowed} objects have a per-user server that shadows the machine-wide one\n");
+    }
+
+    let views: Vec<REG_SAM_FLAGS> = views_to_scan.iter().map(|(_, flag)| *flag).collect();
+    if let Some(ref path) = args.cache {
+        // Only a complete, fresh scan is worth caching
+        if !cancelled && !timed_out && !views_to_read.is_empty() {
+            let objects = all_objects.values().cloned();
+            match cache::save(path, args.root, &view_names, &scan_stats, objects) {
+                Ok(()) => println!("Saved the scan to cache {path}\n"),
+                Err(e) => eprintln!("Failed to write cache {path}: {e}\n"),
+            }
+        }
+        // The full scan skipped the scopes, which `--only-user-scope` filters on
+        if criteria.only_user_scope {
+            read_registration_scopes(&mut all_objects, &views, &mut scan_stats);
+            for (_, objects) in &mut view_results {
+                read_registration_scopes(objects, &views, &mut scan_stats);
+            }
+        }
+        all_objects.retain(|_, obj| should_include_object(obj, &criteria));
+        for (_, objects) in &mut view_results {
+            objects.retain(|_, obj| should_include_object(obj, &criteria));
+        }
+        scan_stats.matched = all_objects.len();
+    }
+
+    if let Some(ref handlers) = shell_handlers {
+        shell_ext::annotate(&mut all_objects, handlers);
+    }
+    if !args.extra_value.is_empty() {
+        extra_values(&mut all_objects, &args, &views_to_scan, &mut scan_stats);
+    }
+    if args.follow_treatas {
+        follow_treat_as(&mut all_objects, &args, &views_to_scan, &mut scan_stats);
+    }
+    if args.check_acls || args.only_writable || args.suspicious {
+        check_acls(&mut all_objects, &args, &views_to_scan, &mut scan_stats);
+        if args.only_writable {
+            all_objects.retain(|_, obj| obj.writable_by_users);
+            scan_stats.matched = all_objects.len();
+        }
+    }
+    if args.check_binary_perms || args.suspicious {
+        acl::check_binary_perms(&mut all_objects);
+    }
+    if args.check_loaded {
+        check_loaded(&mut all_objects, &args);
+    }
+    check_vendors(&mut all_objects, &args);
+    if let Some(ref filter) = args.filter_signer {
+        all_objects.retain(|_, obj| signer_matches(obj, filter));
+        scan_stats.matched = all_objects.len();
+    }
+    if args.suspicious {
+        security_scan::score_objects(&mut all_objects);
+        all_objects.retain(|_, obj| obj.suspicion_score >= args.suspicion_threshold);
+        scan_stats.matched = all_objects.len();
+    }
+
+    // Each view kept its own sample, or nothing was sampled yet if filters followed the scan;
+    // the same keys cut the merged set down to the sample of the whole
+    if let (Some(size), Some(seed)) = (args.sample, sample_seed) {
+        sample::retain(&mut all_objects, size as usize, seed);
+    }
+
+    // Each view stops early at the limit; cap the merged set too so both views together
+    // never return more than `--limit` objects
+    apply_limit(&mut all_objects, args.limit, &export_options);
+    // Only `--only-user-scope` reads the scope during the scan; the listed objects get it now
+    read_registration_scopes(&mut all_objects, &views, &mut scan_stats);
+    // Instantiating runs foreign code, so only the objects that are listed are probed
+    if args.probe {
+        probe_objects(&mut all_objects, &args);
+        // Objects a Ctrl+C left unprobed match neither filter
+        if args.only_instantiable || args.only_not_instantiable {
+            all_objects.retain(|_, obj| obj.instantiable == Some(args.only_instantiable));
+            scan_stats.matched = all_objects.len();
+        }
+        // The scan rated the objects before the probe decided their usability; rate them again
+        if let Some(min_usability) = args.min_usability {
+            let scorer = &*export_options.usability;
+            all_objects.retain(|_, obj| scorer.score(obj).level >= min_usability);
+            scan_stats.matched = all_objects.len();
+        }
+    }
+
+    if let Some(ref path) = args.stats_json {
+        let summary = analysis::compute_summary(&all_objects, &view_clsids);
+        let metrics = analysis::ScanMetrics::new(summary, scan_seconds, &view_names);
+        export_stats_json(path, &metrics, &export_options);
+    }
+
+    if args.fingerprint {
+        println!("{}", analysis::fingerprint(&all_objects)?);
+        return Ok(EXIT_SUCCESS);
+    }
+
+    if let Some(ref path) = args.baseline {
+        let baseline = compare::Baseline::load(path)?;
+        let deviations = compare::compare_to_baseline(&all_objects, &baseline);
+        report_baseline_deviations(path, &baseline, &deviations, &export_options);
+        return Ok(if deviations.is_empty() {
+            EXIT_SUCCESS
+        } else {
+            EXIT_DEVIATIONS
+        });
+    }
+
     // Display results
-    display_results(&all_objects, args.verbose)?;
+    display_results(&all_objects, &scan_stats, &view_clsids, args.verbose, &export_options)?;
+    if args.report_duplicates {
+        report_duplicate_prog_ids(&all_objects, args.raw_values);
+    }
+    if args.report_multi_progid {
+        let index = prog_id_index(&args, &views_to_scan);
+        report_multi_prog_ids(&all_objects, &index, args.raw_values);
+    }
+    group_by_vendor(&all_objects, &args, &export_options);
+    if args.report_view_conflicts {
+        match view_results.as_slice() {
+            [first, second] => report_view_conflicts(first, second, args.raw_values),
+            _ => eprintln!("--report-view-conflicts needs both the 32-bit and 64-bit views\n"),
+        }
+    }
 
-    prompt_export(&all_objects)?;
+    if args.non_interactive {
+        deliver_results(&all_objects, &scan_stats, &args, &export_options)?;
+        return Ok(outcome(&all_objects, timed_out));
+    }
 
-    // Wait for user to press 'q' to quit
-    println!("Press 'q' to quit...");
-    let stdin = io::stdin();
-    for line in stdin.lines().map_while(Result::ok) {
-        if line.trim().to_lowercase() == "q" {
-            break;
+    refine_results(
+        &mut all_objects,
+        &criteria,
+        &mut scan_stats,
+        &view_clsids,
+        args.verbose,
+        &export_options,
+    )?;
+    if interrupt::is_interrupted() {
+        return Ok(EXIT_SUCCESS);
+    }
+
+    deliver_results(&all_objects, &scan_stats, &args, &export_options)?;
+
+    wait_for_quit();
+
+    Ok(outcome(&all_objects, timed_out))
+}
+
+/// Exit code of a scan: a time-out first, then whether anything is left to show
+fn outcome(objects: &HashMap<String, ComObject>, timed_out: bool) -> i32 {
+    if timed_out {
+        EXIT_TIMED_OUT
+    } else if objects.is_empty() {
+        EXIT_NO_MATCHES
+    } else {
+        EXIT_SUCCESS
+    }
+}
+
+/// Read the given CLSIDs directly in every view, let `annotate` add mode-specific data, run
+/// the requested post-scan checks, and display and deliver the objects found. Returns the exit
+/// code.
+fn run_lookup(
+    args: &Args,
+    clsids: &[String],
+    views_to_scan: &[(&str, REG_SAM_FLAGS)],
+    export_options: &ExportOptions,
+    annotate: impl FnOnce(&mut HashMap<String, ComObject>),
+) -> Result<i32> {
+    let mut objects = HashMap::new();
+    let mut missing = Vec::new();
+    let mut stats = ScanStats::default();
+    let mut view_clsids: Vec<(&str, HashSet<String>)> =
+        views_to_scan.iter().map(|(view_name, _)| (*view_name, HashSet::new())).collect();
+
+    for clsid in clsids {
+        let mut found = false;
+        for ((view_name, view_flag), (_, found_in_view)) in
+            views_to_scan.iter().zip(&mut view_clsids)
+        {
+            match lookup_com_object(args.root, *view_flag, clsid, &mut stats) {
+                Ok(Some(obj)) => {
+                    found = true;
+                    found_in_view.insert(obj.clsid.clone());
+                    merge_objects(&mut objects, [obj]);
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("Error reading {view_name} view: {e}"),
+            }
+        }
+        if !found {
+            missing.push(clsid.as_str());
         }
     }
 
+    // The CLSIDs of `--bho` and `--shell-ext` come from the registry
+    for clsid in &missing {
+        let clsid = escape_controls(clsid, args.raw_values);
+        println!("CLSID {clsid} is not registered in the scanned registry views.");
+    }
+    if !missing.is_empty() {
+        println!();
+    }
+
+    annotate(&mut objects);
+    if !args.extra_value.is_empty() {
+        extra_values(&mut objects, args, views_to_scan, &mut stats);
+    }
+    if args.follow_treatas {
+        follow_treat_as(&mut objects, args, views_to_scan, &mut stats);
+    }
+    if args.check_acls || args.only_writable || args.suspicious {
+        check_acls(&mut objects, args, views_to_scan, &mut stats);
+    }
+    if args.check_binary_perms || args.suspicious {
+        acl::check_binary_perms(&mut objects);
+    }
+    if args.check_loaded {
+        check_loaded(&mut objects, args);
+    }
+    check_vendors(&mut objects, args);
+    if args.suspicious {
+        security_scan::score_objects(&mut objects);
+    }
+    if args.probe {
+        probe_objects(&mut objects, args);
+    }
+    report_inaccessible_keys(&stats, &[]);
+
+    stats.enumerated = clsids.len();
+    stats.matched = clsids.len() - missing.len();
+    if !objects.is_empty() {
+        let verbose = args.verbose.max(2);
+        display_results(&objects, &stats, &view_clsids, verbose, export_options)?;
+        group_by_vendor(&objects, args, export_options);
+        deliver_results(&objects, &stats, args, export_options)?;
+    }
+
+    if !args.non_interactive {
+        wait_for_quit();
+    }
+    Ok(outcome(&objects, false))
+}
+
+/// Print the `--dump` of the CLSID key in every view. Returns the exit code.
+fn dump_clsid(args: &Args, clsid: &str, views_to_scan: &[(&str, REG_SAM_FLAGS)]) -> i32 {
+    let mut stats = ScanStats::default();
+    let mut found = false;
+    let mut failed = false;
+    for (view_name, view_flag) in views_to_scan {
+        match dump_clsid_key(args.root, *view_flag, clsid, &mut stats) {
+            Ok(Some(dump)) => {
+                found = true;
+                display_key_dump(view_name, &dump, args.raw_values);
+            }
+            Ok(None) => println!("CLSID {clsid} is not registered in the {view_name} view.\n"),
+            Err(e) => {
+                failed = true;
+                eprintln!("Error reading {view_name} view: {e}");
+            }
+        }
+    }
+    report_inaccessible_keys(&stats, &[]);
+
+    if found {
+        EXIT_SUCCESS
+    } else if failed {
+        EXIT_ERROR
+    } else {
+        EXIT_NO_MATCHES
+    }
+}
+
+/// Read the interfaces of every view, merging interfaces registered in both by IID and
+/// sorting them by name (unnamed ones last, by IID)
+fn read_interfaces(
+    args: &Args,
+    views_to_scan: &[(&str, REG_SAM_FLAGS)],
+) -> ResultSet<InterfaceInfo> {
+    let mut interfaces: HashMap<String, InterfaceInfo> = HashMap::new();
+    let mut stats = ScanStats::default();
+    for (view_name, view_flag) in views_to_scan {
+        if !args.quiet {
+            println!("Scanning {view_name} interfaces...");
+        }
+        match scan_interfaces(args.root, *view_flag, interrupt::flag(), &mut stats) {
+            Ok(found) => {
+                for interface in found {
+                    match interfaces.entry(normalize_clsid(&interface.iid)) {
+                        Entry::Occupied(mut entry) => {
+                            let existing = entry.get_mut();
+                            existing.name = existing.name.take().or(interface.name);
+                            existing.proxy_stub_clsid =
+                                existing.proxy_stub_clsid.take().or(interface.proxy_stub_clsid);
+                            existing.type_lib = existing.type_lib.take().or(interface.type_lib);
+                        }
+                        Entry::Vacant(entry) => {
+                            entry.insert(interface);
+                        }
+                    }
+                }
+            }
+            Err(e) => eprintln!("Error scanning {view_name} interfaces: {e}"),
+        }
+    }
+    if !args.quiet {
+        println!();
+    }
+
+    let mut records: Vec<InterfaceInfo> = interfaces.into_values().collect();
+    records.sort_by(|a, b| match (&a.name, &b.name) {
+        (Some(x), Some(y)) => x.to_lowercase().cmp(&y.to_lowercase()),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.iid.cmp(&b.iid),
+    });
+    stats.matched = records.len();
+    ResultSet {
+        title: "Interfaces",
+        records,
+        stats,
+    }
+}
+
+/// Read the shell extension registrations of every view, reporting keys that could not be read
+fn shell_extensions(args: &Args, views_to_scan: &[(&str, REG_SAM_FLAGS)]) -> Vec<ShellExtension> {
+    let mut extensions = Vec::new();
+    let mut stats = ScanStats::default();
+    for (view_name, view_flag) in views_to_scan {
+        match read_shell_extensions(args.root, *view_flag, &mut stats) {
+            Ok(found) => extensions.extend(found),
+            Err(e) => eprintln!("Error reading {view_name} shell extensions: {e}"),
+        }
+    }
+    report_inaccessible_keys(&stats, &[]);
+    extensions
+}
+
+/// Read the CLSIDs registered as Browser Helper Objects in every view, without duplicates
+fn browser_helper_objects(views_to_scan: &[(&str, REG_SAM_FLAGS)]) -> Vec<String> {
+    let mut clsids: Vec<String> = Vec::new();
+    for (view_name, view_flag) in views_to_scan {
+        match read_browser_helper_objects(*view_flag) {
+            Ok(found) => {
+                for clsid in found {
+                    if !clsids.iter().any(|c| same_guid(c, &clsid)) {
+                        clsids.push(clsid);
+                    }
+                }
+            }
+            Err(e) => eprintln!("Error reading {view_name} Browser Helper Objects: {e}"),
+        }
+    }
+    clsids
+}
+
+/// Hand the final results to `--clipboard` and/or `--output`; without either,
+/// offer the interactive export prompt
+fn deliver_results(
+    objects: &HashMap<String, ComObject>,
+    stats: &ScanStats,
+    args: &Args,
+    options: &ExportOptions,
+) -> Result<()> {
+    if args.clipboard {
+        copy_to_clipboard(objects, stats, options);
+    }
+    if let Some(ref path) = args.output {
+        export_to_path(objects, stats, path, options);
+    }
+    if !args.clipboard && args.output.is_none() && !args.non_interactive {
+        prompt_export(objects, stats, options)?;
+    }
     Ok(())
 }
+
+/// Copy the formatted export to the clipboard and report how much was copied
+fn copy_to_clipboard(
+    objects: &HashMap<String, ComObject>,
+    stats: &ScanStats,
+    options: &ExportOptions,
+) {
+    match copy_export_to_clipboard(objects, stats, options) {
+        Ok(bytes) => println!("Copied {bytes} bytes ({}) to the clipboard", options.format),
+        Err(e) => eprintln!("Failed to copy to the clipboard: {e}"),
+    }
+}
+
+/// Run the `--check-acls` pass over the collected objects in every scanned view
+fn check_acls(
+    objects: &mut HashMap<String, ComObject>,
+    args: &Args,
+    views_to_scan: &[(&str, REG_SAM_FLAGS)],
+    stats: &mut ScanStats,
+) {
+    let views: Vec<REG_SAM_FLAGS> = views_to_scan.iter().map(|(_, flag)| *flag).collect();
+    if let Err(e) = check_key_acls(objects, args.root, &views, stats) {
+        eprintln!("Error checking CLSID key ACLs: {e}");
+    }
+}
+
+/// Number of scan workers for `--threads`, where 0 means one per CPU
+fn scan_threads(args: &Args) -> usize {
+    match args.threads {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    }
+}
+
+/// Index the ProgIDs of the classes root in every scanned view for `--report-multi-progid`
+fn prog_id_index(args: &Args, views_to_scan: &[(&str, REG_SAM_FLAGS)]) -> Vec<(String, String)> {
+    let mut index = Vec::new();
+    let mut stats = ScanStats::default();
+    for (view_name, view_flag) in views_to_scan {
+        match read_prog_id_index(args.root, *view_flag, &mut stats) {
+            Ok(entries) => index.extend(entries),
+            Err(e) => eprintln!("Error reading ProgIDs in {view_name} view: {e}"),
+        }
+    }
+    index
+}
+
+/// Read the `--extra-value` values of every collected object in every scanned view
+fn extra_values(
+    objects: &mut HashMap<String, ComObject>,
+    args: &Args,
+    views_to_scan: &[(&str, REG_SAM_FLAGS)],
+    stats: &mut ScanStats,
+) {
+    let views: Vec<REG_SAM_FLAGS> = views_to_scan.iter().map(|(_, flag)| *flag).collect();
+    if let Err(e) = read_extra_values(objects, args.root, &views, &args.extra_value, stats) {
+        eprintln!("Error reading extra values: {e}");
+    }
+}
+
+/// Run the `--follow-treatas` pass and report how many redirections were resolved
+fn follow_treat_as(
+    objects: &mut HashMap<String, ComObject>,
+    args: &Args,
+    views_to_scan: &[(&str, REG_SAM_FLAGS)],
+    stats: &mut ScanStats,
+) {
+    let views: Vec<REG_SAM_FLAGS> = views_to_scan.iter().map(|(_, flag)| *flag).collect();
+    match resolve_treat_as(objects, args.root, &views, stats) {
+        Ok(cycles) if !args.quiet => {
+            let resolved = objects.values().filter(|obj| obj.treat_as_target.is_some()).count();
+            println!("Resolved {resolved} TreatAs redirections ({cycles} cut at a cycle)\n");
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Error following TreatAs redirections: {e}"),
+    }
+}
+
+/// Run the `--check-loaded` pass and report how many processes were inspected
+fn check_loaded(objects: &mut HashMap<String, ComObject>, args: &Args) {
+    let processes = modules::check_loaded(objects);
+    if !args.quiet {
+        let loaded = objects.values().filter(|obj| obj.currently_loaded).count();
+        println!("{loaded} objects have their DLL loaded ({processes} processes inspected)\n");
+    }
+}
+
+/// Run the `--probe` pass and report how many of the probed objects could be created
+fn probe_objects(objects: &mut HashMap<String, ComObject>, args: &Args) {
+    let probed = probe::probe_objects(objects);
+    if !args.quiet {
+        let created = objects.values().filter(|obj| obj.instantiable == Some(true)).count();
+        println!("{created} of {probed} probed objects could be instantiated\n");
+    }
+}
+
+/// Run the signer and version resource passes requested directly or needed by
+/// `--filter-signer` and `--group-by-vendor`
+fn check_vendors(objects: &mut HashMap<String, ComObject>, args: &Args) {
+    let vendor = args.group_by_vendor;
+    if args.check_signers || args.filter_signer.is_some() || vendor == Some(VendorSource::Signer) {
+        signature::check_signers(objects);
+    }
+    if args.check_version_info || vendor == Some(VendorSource::Company) {
+        version_info::check_version_info(objects);
+    }
+}
+
+/// Print the `--group-by-vendor` report and write the `--vendor-export` file
+fn group_by_vendor(objects: &HashMap<String, ComObject>, args: &Args, options: &ExportOptions) {
+    let Some(source) = args.group_by_vendor else {
+        return;
+    };
+    report_vendor_groups(objects, source, options);
+    if let Some(ref path) = args.vendor_export {
+        export_vendor_groups(objects, source, path, options);
+    }
+}
+
+/// Load the `--cache` file for `--use-cache`, reporting why it cannot be used
+fn load_cache(args: &Args, view_names: &[&str]) -> Option<cache::ScanCache> {
+    let path = args.cache.as_deref().filter(|_| args.use_cache)?;
+    match cache::load(path, args.root, view_names, args.cache_max_age) {
+        Ok(cached) => {
+            if !args.quiet {
+                println!("Loaded {} objects from cache {path}\n", cached.objects.len());
+            }
+            Some(cached)
+        }
+        Err(e) => {
+            eprintln!("Not using the cache, scanning instead: {e}\n");
+            None
+        }
+    }
+}
+
+/// Merge objects from one registry view into the combined results,
+/// filling in fields that are missing for CLSIDs already seen in another view
+fn merge_objects(
+    all_objects: &mut HashMap<String, ComObject>,
+    objects: impl IntoIterator<Item = ComObject>,
+) {
+    for obj in objects {
+        match all_objects.entry(obj.clsid.clone()) {
+            Entry::Occupied(mut entry) => entry.get_mut().merge(obj),
+            Entry::Vacant(entry) => {
+                entry.insert(obj);
+            }
+        }
+    }
+}
+
+/// Scan all selected views `--bench-iters` times and print one machine-parseable summary line
+fn run_benchmark(
+    args: &Args,
+    views_to_scan: &[(&str, REG_SAM_FLAGS)],
+    criteria: &FilterCriteria,
+) {
+    let mut durations = Vec::new();
+    let mut stats = ScanStats::default();
+    let mut objects = 0;
+    let options = ScanOptions {
+        limit: args.limit,
+        threads: scan_threads(args),
+        deadline: None,
+    };
+
+    for _ in 0..args.bench_iters {
+        let mut iteration_stats = ScanStats::default();
+        let mut iteration_objects: HashMap<String, ComObject> = HashMap::new();
+
+        let start = Instant::now();
+        for (view_name, view_flag) in views_to_scan {
+            match scan_com_objects(args.root, *view_flag, options, criteria) {
+                Ok((found, view_stats)) => {
+                    iteration_stats.add(&view_stats);
+                    merge_objects(&mut iteration_objects, found.into_values());
+                }
+                Err(e) => eprintln!("Error scanning {view_name} view: {e}"),
+            }
+        }
+        durations.push(start.elapsed().as_secs_f64());
+
+        stats = iteration_stats;
+        objects = iteration_objects.len();
+    }
+
+    durations.sort_by(f64::total_cmp);
+    let min = durations[0];
+    let max = durations[durations.len() - 1];
+    let mean = durations.iter().sum::<f64>() / durations.len() as f64;
+    let mid = durations.len() / 2;
+    let median = if durations.len() % 2 == 0 {
+        (durations[mid - 1] + durations[mid]) / 2.0
+    } else {
+        durations[mid]
+    };
+    let objects_per_sec = if mean > 0.0 {
+        stats.enumerated as f64 / mean
+    } else {
+        0.0
+    };
+
+    println!(
+        "bench iters={} enumerated={} matched={} objects={} min={:.4}s median={:.4}s max={:.4}s mean={:.4}s objects_per_sec={:.0}",
+        durations.len(),
+        stats.enumerated,
+        stats.matched,
+        objects,
+        min,
+        median,
+        max,
+        mean,
+        objects_per_sec
+    );
+}
+
+/// Read one line from stdin, returning `None` if it is empty or Ctrl+C was pressed
+fn read_optional_line() -> Result<Option<String>> {
+    Ok(interrupt::read_line()?.filter(|input| !input.is_empty()))
+}
+
+/// Let the user repeatedly narrow the collected results in memory without re-scanning.
+/// Refinement filters use the same matching options (case, fuzzy) as the scan, and
+/// `stats.matched` follows the refined set.
+fn refine_results(
+    objects: &mut HashMap<String, ComObject>,
+    base: &FilterCriteria,
+    stats: &mut ScanStats,
+    views: &[(&str, HashSet<String>)],
+    verbose: u8,
+    options: &ExportOptions,
+) -> Result<()> {
+    loop {
+        println!("Enter a new filter to narrow the current results, or press Enter to continue:");
+        let filter = match read_optional_line()? {
+            Some(filter) => filter,
+            None => return Ok(()),
+        };
+
+        let criteria = FilterCriteria {
+            interactive: Some(filter),
+            fuzzy_threshold: base.fuzzy_threshold,
+            case_sensitive: base.case_sensitive,
+            ..FilterCriteria::default()
+        };
+        objects.retain(|_, obj| should_include_object(obj, &criteria));
+        stats.matched = objects.len();
+
+        display_results(objects, stats, views, verbose, options)?;
+    }
+}
+
+/// Wait for user to press 'q' to quit; Ctrl+C or end of input also exits
+fn wait_for_quit() {
+    if interrupt::is_interrupted() {
+        return;
+    }
+
+    println!("Press 'q' to quit...");
+    while let Ok(Some(line)) = interrupt::read_line() {
+        if line.to_lowercase() == "q" {
+            break;
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Loaded-module lookup for `--check-loaded`.
+//!
+//! Takes a Toolhelp snapshot of every process and its modules, then marks objects whose
+//! `InprocServer32` DLL is currently loaded somewhere. Paths are compared after expanding
+//! environment variables, resolving 8.3 short names, and lowercasing.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use windows::core::HSTRING;
+use windows::Win32::Foundation::{CloseHandle, HANDLE};
+use windows::Win32::Storage::FileSystem::GetLongPathNameW;
+use windows::Win32::System::Diagnostics::ToolHelp::{
+    CreateToolhelp32Snapshot, Module32FirstW, Module32NextW, Process32FirstW, Process32NextW,
+    MODULEENTRY32W, PROCESSENTRY32W, TH32CS_SNAPMODULE, TH32CS_SNAPMODULE32, TH32CS_SNAPPROCESS,
+};
+
+use crate::server_path::server_binary;
+use crate::types::ComObject;
+
+/// Normalized full paths and lowercase file names of every module loaded in any process
+/// this user can inspect
+struct LoadedModules {
+    paths: HashSet<String>,
+    file_names: HashSet<String>,
+}
+
+/// Sets `currently_loaded` on every object whose InprocServer32 DLL is loaded in a running
+/// process. Returns the number of processes whose modules could be listed.
+pub fn check_loaded(objects: &mut HashMap<String, ComObject>) -> usize {
+    let (modules, processes) = loaded_modules();
+    for obj in objects.values_mut() {
+        obj.currently_loaded = obj
+            .inproc_server
+            .as_deref()
+            .and_then(server_binary)
+            .is_some_and(|path| {
+                if path.is_absolute() {
+                    modules.paths.contains(&normalize(&path))
+                } else {
+                    // Bare names are found through the search path; match any loaded copy
+                    modules.file_names.contains(&path.to_string_lossy().to_lowercase())
+                }
+            });
+    }
+    processes
+}
+
+/// Snapshot the modules of all processes. Processes that cannot be opened (protected or
+/// owned by other users without elevation) are skipped.
+fn loaded_modules() -> (LoadedModules, usize) {
+    let mut modules = LoadedModules {
+        paths: HashSet::new(),
+        file_names: HashSet::new(),
+    };
+    let mut processes = 0;
+
+    for pid in process_ids() {
+        let flags = TH32CS_SNAPMODULE | TH32CS_SNAPMODULE32;
+        let Ok(snapshot) = (unsafe { CreateToolhelp32Snapshot(flags, pid) }) else {
+            continue;
+        };
+        processes += 1;
+
+        let mut entry = MODULEENTRY32W {
+            dwSize: std::mem::size_of::<MODULEENTRY32W>() as u32,
+            ..Default::default()
+        };
+        let mut next = unsafe { Module32FirstW(snapshot, &mut entry) };
+        while next.is_ok() {
+            let path = wide_to_string(&entry.szExePath);
+            let path = Path::new(&path);
+            if let Some(name) = path.file_name() {
+                modules.file_names.insert(name.to_string_lossy().to_lowercase());
+            }
+            modules.paths.insert(normalize(path));
+            next = unsafe { Module32NextW(snapshot, &mut entry) };
+        }
+        close(snapshot);
+    }
+    (modules, processes)
+}
+
+/// IDs of all running processes
+fn process_ids() -> Vec<u32> {
+    let mut ids = Vec::new();
+    let Ok(snapshot) = (unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) }) else {
+        return ids;
+    };
+
+    let mut entry = PROCESSENTRY32W {
+        dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+        ..Default::default()
+    };
+    let mut next = unsafe { Process32FirstW(snapshot, &mut entry) };
+    while next.is_ok() {
+        // PID 0 is the idle process, which has no modules
+        if entry.th32ProcessID != 0 {
+            ids.push(entry.th32ProcessID);
+        }
+        next = unsafe { Process32NextW(snapshot, &mut entry) };
+    }
+    close(snapshot);
+    ids
+}
+
+/// Comparable form of a path: 8.3 short names expanded (when the file exists), the `\\?\`
+/// prefix dropped, and lowercased
+fn normalize(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let name = HSTRING::from(path.as_ref());
+    let mut buffer = [0u16; 1024];
+    let len = unsafe { GetLongPathNameW(&name, Some(&mut buffer)) } as usize;
+    let long = if len > 0 && len < buffer.len() {
+        String::from_utf16_lossy(&buffer[..len])
+    } else {
+        path.into_owned()
+    };
+    long.trim_start_matches(r"\\?\").to_lowercase()
+}
+
+/// Converts a null-terminated UTF-16 buffer to a string
+fn wide_to_string(wide: &[u16]) -> String {
+    let len = wide.iter().position(|&c| c == 0).unwrap_or(wide.len());
+    String::from_utf16_lossy(&wide[..len])
+}
+
+fn close(snapshot: HANDLE) {
+    unsafe {
+        let _ = CloseHandle(snapshot);
+    }
+}
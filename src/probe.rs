@@ -0,0 +1,65 @@
+//! Instantiation probe for `--probe`.
+//!
+//! Asks COM to create every collected class with `CoCreateInstance`, which is the only way to
+//! know for sure that a registration works. This loads the `InprocServer32` DLL into this
+//! process or starts the `LocalServer32` executable, so it is opt-in and runs on the final,
+//! filtered result set only. The created object is released right away.
+
+use std::collections::HashMap;
+use windows::core::{IUnknown, HSTRING};
+use windows::Win32::System::Com::{
+    CLSIDFromString, CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER,
+    CLSCTX_LOCAL_SERVER, COINIT_APARTMENTTHREADED,
+};
+
+use crate::interrupt;
+use crate::types::ComObject;
+
+/// Initializes COM on the current thread and uninitializes it when dropped, if the
+/// initialization succeeded (also when COM was already initialized, which must be balanced too)
+struct ComApartment {
+    initialized: bool,
+}
+
+impl ComApartment {
+    fn enter() -> Self {
+        let result = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) };
+        Self {
+            initialized: result.is_ok(),
+        }
+    }
+}
+
+impl Drop for ComApartment {
+    fn drop(&mut self) {
+        if self.initialized {
+            unsafe { CoUninitialize() };
+        }
+    }
+}
+
+/// Sets `instantiable` on every object, stopping early on Ctrl+C. Returns how many objects
+/// were probed.
+pub fn probe_objects(objects: &mut HashMap<String, ComObject>) -> usize {
+    let _apartment = ComApartment::enter();
+    let mut probed = 0;
+    for obj in objects.values_mut() {
+        if interrupt::is_interrupted() {
+            break;
+        }
+        obj.instantiable = Some(instantiate(&obj.clsid));
+        probed += 1;
+    }
+    probed
+}
+
+/// True if COM creates the class in-process or in a local server
+fn instantiate(clsid: &str) -> bool {
+    unsafe {
+        let Ok(guid) = CLSIDFromString(&HSTRING::from(clsid)) else {
+            return false;
+        };
+        CoCreateInstance::<_, IUnknown>(&guid, None, CLSCTX_INPROC_SERVER | CLSCTX_LOCAL_SERVER)
+            .is_ok()
+    }
+}
@@ -4,61 +4,1524 @@
 //! including CLSID enumeration, ProgID lookup, and object descriptions.
 
 use anyhow::Result;
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::Instant;
 use windows::core::{HSTRING, PCWSTR, PWSTR};
-use windows::Win32::Foundation::ERROR_SUCCESS;
+use windows::Win32::Foundation::{
+    ERROR_ACCESS_DENIED, ERROR_FILE_NOT_FOUND, ERROR_MORE_DATA, ERROR_NO_MORE_ITEMS,
+    ERROR_SUCCESS,
+};
 use windows::Win32::System::Registry::{
-    RegCloseKey, RegEnumKeyExW, RegOpenKeyExW, RegQueryValueExW, HKEY, HKEY_CLASSES_ROOT,
-    KEY_READ, REG_SAM_FLAGS, REG_VALUE_TYPE,
+    RegCloseKey, RegEnumKeyExW, RegEnumValueW, RegOpenKeyExW, RegQueryInfoKeyW, RegQueryValueExW,
+    HKEY, HKEY_CLASSES_ROOT,
+    HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, KEY_READ, REG_SAM_FLAGS, REG_VALUE_TYPE,
+};
+
+use chrono::{DateTime, Utc};
+use windows::Win32::Foundation::FILETIME;
+
+use windows::Win32::Security::{DACL_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR};
+use windows::Win32::System::Registry::RegGetKeySecurity;
+
+use crate::acl::grants_write_to_users;
+use crate::server_path::is_unquoted_with_spaces;
+use crate::categories::{is_safe_for_scripting, well_known_category_name};
+use crate::types::{
+    ComObject, ComponentCategory, InterfaceInfo, KeyDump, RawValue, RegistrationScope,
+    RegistryRoot, ScanStats, ShellExtension, TreatAsTarget, TypeLibPlatform, TypeLibVersion,
+    ValueTypes,
 };
+use crate::interfaces::{well_known_interface_name, IidResolver};
+use crate::shell_ext::handler_type_name;
+use crate::filter::{normalize_clsid, should_include_object, FilterCriteria};
+
+/// Characters of the value buffer each thread starts with; enough for nearly every
+/// ProgID, description and server path
+const VALUE_BUFFER_CHARS: usize = 512;
+
+thread_local! {
+    /// Scratch buffer shared by every string value read on this thread
+    static VALUE_BUFFER: RefCell<Vec<u16>> = RefCell::new(vec![0; VALUE_BUFFER_CHARS]);
+}
+
+/// Cache of resolved category names, keyed by upper-case CATID
+type CategoryNames = HashMap<String, Option<String>>;
+
+/// Lookups shared by many objects, resolved only once per scan
+#[derive(Debug, Default)]
+struct LookupCaches {
+    category_names: CategoryNames,
+    /// Interface names of the IID subkeys of `shellex` keys
+    interface_names: IidResolver,
+    /// `RunAs` identity of each AppID, keyed by upper-case AppID
+    run_as: HashMap<String, Option<String>>,
+}
+
+/// Rights that let the holder modify a registry key: `KEY_SET_VALUE`, `KEY_CREATE_SUB_KEY`,
+/// `DELETE`, `WRITE_DAC` and `WRITE_OWNER`
+const KEY_WRITE_RIGHTS: u32 = 0x0002 | 0x0004 | 0x0001_0000 | 0x0004_0000 | 0x0008_0000;
+
+/// Key below `HKEY_LOCAL_MACHINE` listing the Browser Helper Objects that Internet Explorer
+/// (and Explorer on older Windows) loads, one CLSID subkey per BHO
+const BHO_KEY: &str =
+    "SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Explorer\\Browser Helper Objects";
+
+/// Key below `HKEY_LOCAL_MACHINE` whose values name every approved shell extension CLSID
+const APPROVED_SHELL_EXT_KEY: &str =
+    "SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Shell Extensions\\Approved";
+
+/// Key below `HKEY_LOCAL_MACHINE` with one subkey per icon overlay handler
+const ICON_OVERLAY_KEY: &str =
+    "SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Explorer\\ShellIconOverlayIdentifiers";
+
+/// Classes whose `shellex` key is not a direct child of a top-level classes root key
+const NESTED_SHELLEX_CLASSES: &[&str] = &["Directory\\Background"];
+
+/// How many CLSIDs are processed between two progress callbacks
+const PROGRESS_INTERVAL: usize = 256;
+
+/// How a view is scanned: the early stops and the number of worker threads
+#[derive(Debug, Clone, Copy)]
+pub struct ScanOptions {
+    /// Stop once this many objects matched in the view (0 = no limit)
+    pub limit: usize,
+    /// Worker threads reading CLSID keys; 1 scans on the calling thread
+    pub threads: usize,
+    /// `--timeout`: stop before the next CLSID once this instant has passed
+    pub deadline: Option<Instant>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            limit: 0,
+            threads: 1,
+            deadline: None,
+        }
+    }
+}
+
+impl ScanOptions {
+    /// True once the deadline, if any, has passed
+    pub fn expired(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+/// Scans the Windows registry for COM objects with specified filters.
+/// Returns the matching objects along with enumeration counters.
+/// A non-zero `options.limit` stops the enumeration early once that many objects matched in
+/// this view; the final cap across views is applied after merging.
+pub fn scan_com_objects(
+    root: RegistryRoot,
+    view_flag: REG_SAM_FLAGS,
+    options: ScanOptions,
+    criteria: &FilterCriteria,
+) -> Result<(HashMap<String, ComObject>, ScanStats)> {
+    let never_cancelled = AtomicBool::new(false);
+    scan_com_objects_with_progress(root, view_flag, options, criteria, &never_cancelled, |_, _| {})
+}
+
+/// Like [`scan_com_objects`], but periodically reports `(processed, matched)` counts
+/// to `on_progress` and stops early, keeping what was collected, once `cancel` is set
+pub fn scan_com_objects_with_progress(
+    root: RegistryRoot,
+    view_flag: REG_SAM_FLAGS,
+    options: ScanOptions,
+    criteria: &FilterCriteria,
+    cancel: &AtomicBool,
+    on_progress: impl FnMut(usize, usize),
+) -> Result<(HashMap<String, ComObject>, ScanStats)> {
+    let mut objects = HashMap::new();
+    let stats = scan_com_objects_with(
+        root,
+        view_flag,
+        options,
+        criteria,
+        cancel,
+        |obj| {
+            objects.insert(obj.clsid.clone(), obj);
+        },
+        on_progress,
+    )?;
+
+    Ok((objects, stats))
+}
+
+/// Streaming core of the scan: hands each matching object to `on_object` as soon as it is read,
+/// without collecting them, so consumers can export or count in constant memory.
+/// `on_progress` receives `(processed, matched)` every [`PROGRESS_INTERVAL`] CLSIDs and once
+/// more when the scan ends. `options.limit` has the same meaning as in [`scan_com_objects`].
+/// The enumeration stops before the next CLSID once `cancel` is set (e.g. from a Ctrl+C
+/// handler) or `options.deadline` has passed; the `CLSID` key is still closed and the objects
+/// seen so far are kept. With more than one thread the work is split as described in
+/// [`scan_parallel`].
+pub fn scan_com_objects_with(
+    root: RegistryRoot,
+    view_flag: REG_SAM_FLAGS,
+    options: ScanOptions,
+    criteria: &FilterCriteria,
+    cancel: &AtomicBool,
+    mut on_object: impl FnMut(ComObject),
+    mut on_progress: impl FnMut(usize, usize),
+) -> Result<ScanStats> {
+    if options.threads > 1 {
+        return scan_parallel(root, view_flag, options, criteria, cancel, on_object, on_progress);
+    }
+
+    let limit = options.limit;
+    let mut scanner = ComObjectScanner::new(root, view_flag, criteria.only_user_scope)?;
+    let mut processed = 0;
+    let mut matched = 0;
+
+    while !cancel.load(Ordering::Relaxed) && !options.expired() {
+        let Some(obj) = scanner.next() else {
+            break;
+        };
+        // A failed entry is counted in the scanner's stats, like a key a worker cannot open
+        let Ok(obj) = obj else {
+            continue;
+        };
+        processed += 1;
+
+        // Check if this object passes all filters
+        let include = should_include_object(&obj, criteria);
+        if include {
+            matched += 1;
+            on_object(obj);
+        }
+
+        if processed % PROGRESS_INTERVAL == 0 {
+            on_progress(processed, matched);
+        }
+
+        // Check limit (CLSIDs are unique within a view, so matches count objects)
+        if include && limit > 0 && matched >= limit {
+            break;
+        }
+    }
+
+    on_progress(processed, matched);
+
+    Ok(ScanStats {
+        matched,
+        ..scanner.stats()
+    })
+}
+
+/// Parallel scan of one view. The CLSID names are enumerated first, which is cheap, and split
+/// into one contiguous chunk per worker. Each worker opens its own `CLSID` key, reads and
+/// filters its chunk, and sends the outcome of every CLSID back over a channel. The calling
+/// thread collects them, applies the limit (telling the workers to stop once it is reached)
+/// and runs the callbacks, so `on_object` and `on_progress` never run on a worker. Which
+/// objects a limit keeps depends on timing; `apply_limit` makes the final cut in sort order.
+fn scan_parallel(
+    root: RegistryRoot,
+    view_flag: REG_SAM_FLAGS,
+    options: ScanOptions,
+    criteria: &FilterCriteria,
+    cancel: &AtomicBool,
+    mut on_object: impl FnMut(ComObject),
+    mut on_progress: impl FnMut(usize, usize),
+) -> Result<ScanStats> {
+    let hkey_clsid = open_clsid_key(root, view_flag)?;
+    let names = enum_subkey_names(hkey_clsid);
+    unsafe {
+        let _ = RegCloseKey(hkey_clsid);
+    }
+
+    let chunk_size = names.len().div_ceil(options.threads).max(1);
+    let stop = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = names
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let sender = sender.clone();
+                let stop = &stop;
+                scope.spawn(move || {
+                    scan_chunk(root, view_flag, chunk, criteria, options, [stop, cancel], sender)
+                })
+            })
+            .collect();
+        // The channel closes once the last worker drops its sender
+        drop(sender);
+
+        let mut processed = 0;
+        let mut matched = 0;
+        for outcome in receiver {
+            processed += 1;
+            if let Some(obj) = outcome {
+                if options.limit == 0 || matched < options.limit {
+                    matched += 1;
+                    on_object(obj);
+                }
+                if options.limit > 0 && matched >= options.limit {
+                    stop.store(true, Ordering::Relaxed);
+                }
+            }
+            if processed % PROGRESS_INTERVAL == 0 {
+                on_progress(processed, matched);
+            }
+        }
+        on_progress(processed, matched);
+
+        let mut stats = ScanStats::default();
+        for worker in workers {
+            let worker_stats = worker
+                .join()
+                .map_err(|_| anyhow::anyhow!("A scan worker thread panicked"))??;
+            stats.add(&worker_stats);
+        }
+        Ok(ScanStats { matched, ..stats })
+    })
+}
+
+/// Reads one chunk of CLSIDs on a worker thread and sends each object, or `None` if it did
+/// not pass the filters, to the collector. Stops before the next CLSID once any of the
+/// `stop` flags is set, the deadline of `options` has passed or the collector is gone.
+fn scan_chunk(
+    root: RegistryRoot,
+    view_flag: REG_SAM_FLAGS,
+    clsids: &[String],
+    criteria: &FilterCriteria,
+    options: ScanOptions,
+    stop: [&AtomicBool; 2],
+    sender: mpsc::Sender<Option<ComObject>>,
+) -> Result<ScanStats> {
+    let hkey_clsid = open_clsid_key(root, view_flag)?;
+    let mut caches = LookupCaches::default();
+    let mut stats = ScanStats::default();
+
+    for clsid in clsids {
+        if stop.iter().any(|flag| flag.load(Ordering::Relaxed)) || options.expired() {
+            break;
+        }
+        stats.enumerated += 1;
+        let obj = read_com_object(
+            root,
+            view_flag,
+            hkey_clsid,
+            clsid.clone(),
+            criteria.only_user_scope,
+            &mut caches,
+            &mut stats,
+        );
+        let include = should_include_object(&obj, criteria);
+        if sender.send(include.then_some(obj)).is_err() {
+            break;
+        }
+    }
+
+    unsafe {
+        let _ = RegCloseKey(hkey_clsid);
+    }
+    Ok(stats)
+}
+
+/// Iterator over every COM object registered under `{root}\CLSID` in one registry view,
+/// unfiltered, so callers can use `.filter()`, `.take()` and `.collect()` directly.
+/// Holds the open `CLSID` key and closes it when dropped.
+pub struct ComObjectScanner {
+    root: RegistryRoot,
+    view_flag: REG_SAM_FLAGS,
+    hkey_clsid: HKEY,
+    index: u32,
+    done: bool,
+    read_scope: bool,
+    // Category names, interface names and AppID identities are shared by many objects
+    caches: LookupCaches,
+    stats: ScanStats,
+}
+
+impl ComObjectScanner {
+    /// Opens the `CLSID` key of the given root and view. With `read_scope` every object also
+    /// gets its [`RegistrationScope`], at the cost of two more key opens per CLSID.
+    pub fn new(root: RegistryRoot, view_flag: REG_SAM_FLAGS, read_scope: bool) -> Result<Self> {
+        Ok(Self {
+            root,
+            view_flag,
+            hkey_clsid: open_clsid_key(root, view_flag)?,
+            index: 0,
+            done: false,
+            read_scope,
+            caches: LookupCaches::default(),
+            stats: ScanStats::default(),
+        })
+    }
+
+    /// Counters accumulated so far: enumerated CLSIDs and keys that could not be opened.
+    /// `matched` stays zero, since the scanner does not filter.
+    pub fn stats(&self) -> ScanStats {
+        self.stats
+    }
+}
+
+impl Iterator for ComObjectScanner {
+    type Item = Result<ComObject>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut name_buffer = [0u16; 256];
+        let mut name_len = name_buffer.len() as u32;
+
+        let result = unsafe {
+            RegEnumKeyExW(
+                self.hkey_clsid,
+                self.index,
+                PWSTR(name_buffer.as_mut_ptr()),
+                &mut name_len,
+                None,
+                PWSTR::null(),
+                None,
+                None,
+            )
+        };
+
+        if result == ERROR_NO_MORE_ITEMS {
+            self.done = true;
+            return None;
+        }
+        if result != ERROR_SUCCESS {
+            self.stats.open_errors += 1;
+            // A name too long for the buffer only affects this subkey; skip it. Any other
+            // failure means the key itself can no longer be enumerated.
+            if result == ERROR_MORE_DATA {
+                self.index += 1;
+            } else {
+                self.done = true;
+            }
+            return Some(Err(anyhow::anyhow!(
+                "Failed to enumerate CLSID key: error code {}",
+                result.0
+            )));
+        }
+
+        self.index += 1;
+        self.stats.enumerated += 1;
+
+        let clsid = String::from_utf16_lossy(&name_buffer[..name_len as usize]);
+        Some(Ok(read_com_object(
+            self.root,
+            self.view_flag,
+            self.hkey_clsid,
+            clsid,
+            self.read_scope,
+            &mut self.caches,
+            &mut self.stats,
+        )))
+    }
+}
+
+impl Drop for ComObjectScanner {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = RegCloseKey(self.hkey_clsid);
+        }
+    }
+}
+
+/// Reads a single CLSID directly, without enumerating the hive.
+/// The GUID is accepted with or without braces; returns `None` if it isn't registered in this view.
+pub fn lookup_com_object(
+    root: RegistryRoot,
+    view_flag: REG_SAM_FLAGS,
+    clsid: &str,
+    stats: &mut ScanStats,
+) -> Result<Option<ComObject>> {
+    let clsid = format!("{{{}}}", normalize_clsid(clsid).to_uppercase());
+    let mut caches = LookupCaches::default();
+
+    unsafe {
+        let hkey_clsid = open_clsid_key(root, view_flag)?;
+
+        let obj = if has_subkey(hkey_clsid, &clsid, stats) {
+            Some(read_com_object(
+                root,
+                view_flag,
+                hkey_clsid,
+                clsid,
+                true,
+                &mut caches,
+                stats,
+            ))
+        } else {
+            None
+        };
+
+        let _ = RegCloseKey(hkey_clsid);
+        Ok(obj)
+    }
+}
+
+/// Reads the key of a single CLSID with every value and subkey below it, for `--dump`.
+/// The GUID is accepted with or without braces; returns `None` if it isn't registered in this view.
+pub fn dump_clsid_key(
+    root: RegistryRoot,
+    view_flag: REG_SAM_FLAGS,
+    clsid: &str,
+    stats: &mut ScanStats,
+) -> Result<Option<KeyDump>> {
+    let clsid = format!("{{{}}}", normalize_clsid(clsid).to_uppercase());
+
+    unsafe {
+        let hkey_clsid = open_clsid_key(root, view_flag)?;
+        let dump = open_key(hkey_clsid, &clsid, KEY_READ, stats).map(|hkey_obj| {
+            let path = format!("{}\\CLSID\\{clsid}", root.path());
+            let dump = dump_key(hkey_obj, path, stats);
+            let _ = RegCloseKey(hkey_obj);
+            dump
+        });
+        let _ = RegCloseKey(hkey_clsid);
+        Ok(dump)
+    }
+}
+
+/// Reads every value of an open key and recurses into its subkeys
+fn dump_key(hkey: HKEY, name: String, stats: &mut ScanStats) -> KeyDump {
+    let values = enum_value_names(hkey)
+        .into_iter()
+        .filter_map(|value_name| read_raw_value(hkey, &value_name))
+        .collect();
+    let subkeys = enum_subkey_names(hkey)
+        .into_iter()
+        .map(|subkey| match open_key(hkey, &subkey, KEY_READ, stats) {
+            Some(hkey_sub) => {
+                let dump = dump_key(hkey_sub, subkey, stats);
+                unsafe {
+                    let _ = RegCloseKey(hkey_sub);
+                }
+                dump
+            }
+            None => KeyDump {
+                name: subkey,
+                readable: false,
+                values: Vec::new(),
+                subkeys: Vec::new(),
+            },
+        })
+        .collect();
+    KeyDump {
+        name,
+        readable: true,
+        values,
+        subkeys,
+    }
+}
+
+/// Reads a value of any type as its type code and raw data bytes
+fn read_raw_value(hkey: HKEY, value_name: &str) -> Option<RawValue> {
+    unsafe {
+        let name = HSTRING::from(value_name);
+        let mut value_type = REG_VALUE_TYPE::default();
+        let mut data = vec![0u8; VALUE_BUFFER_CHARS];
+        loop {
+            let mut size = data.len() as u32;
+            let result = RegQueryValueExW(
+                hkey,
+                &name,
+                None,
+                Some(&mut value_type),
+                Some(data.as_mut_ptr()),
+                Some(&mut size),
+            );
+
+            // `size` now holds the required size in bytes; grow and read again
+            if result == ERROR_MORE_DATA {
+                data.resize(size as usize, 0);
+                continue;
+            }
+            if result != ERROR_SUCCESS {
+                return None;
+            }
+
+            data.truncate(size as usize);
+            return Some(RawValue {
+                name: value_name.to_string(),
+                value_type: value_type.0,
+                data,
+            });
+        }
+    }
+}
+
+/// Lists the CLSIDs registered as Browser Helper Objects in the given registry view.
+/// A missing key means no BHOs are registered.
+pub fn read_browser_helper_objects(view_flag: REG_SAM_FLAGS) -> Result<Vec<String>> {
+    unsafe {
+        let bho_path = HSTRING::from(BHO_KEY);
+        let mut hkey_bho = HKEY::default();
+
+        let result =
+            RegOpenKeyExW(HKEY_LOCAL_MACHINE, &bho_path, 0, KEY_READ | view_flag, &mut hkey_bho);
+        if result == ERROR_FILE_NOT_FOUND {
+            return Ok(Vec::new());
+        }
+        if result != ERROR_SUCCESS {
+            return Err(anyhow::anyhow!(
+                "Failed to open Browser Helper Objects key: error code {}",
+                result.0
+            ));
+        }
+
+        let clsids = enum_subkey_names(hkey_bho);
+        let _ = RegCloseKey(hkey_bho);
+        Ok(clsids)
+    }
+}
+
+/// Collects the shell extension registrations visible in the given registry view: the
+/// approved list, the icon overlay identifiers, and every `shellex` handler registered for a
+/// class, file type or extension below the classes root
+pub fn read_shell_extensions(
+    root: RegistryRoot,
+    view_flag: REG_SAM_FLAGS,
+    stats: &mut ScanStats,
+) -> Result<Vec<ShellExtension>> {
+    let mut extensions = Vec::new();
+    let mut interface_names = IidResolver::new();
+    let mut push = |clsid: String, handler_type: String| {
+        extensions.push(ShellExtension { clsid, handler_type });
+    };
+
+    unsafe {
+        let sam = KEY_READ | view_flag;
+        if let Some(hkey) = open_key(HKEY_LOCAL_MACHINE, APPROVED_SHELL_EXT_KEY, sam, stats) {
+            for clsid in enum_value_names(hkey).iter().filter_map(|name| as_clsid(name)) {
+                push(clsid, "Approved".to_string());
+            }
+            let _ = RegCloseKey(hkey);
+        }
+
+        if let Some(hkey) = open_key(HKEY_LOCAL_MACHINE, ICON_OVERLAY_KEY, sam, stats) {
+            for name in enum_subkey_names(hkey) {
+                let clsid = read_subkey_default(hkey, &name);
+                if let Some(clsid) = clsid.as_deref().and_then(as_clsid) {
+                    push(clsid, "IconOverlayHandler".to_string());
+                }
+            }
+            let _ = RegCloseKey(hkey);
+        }
+
+        let (root_hkey, prefix) = classes_root_key(root);
+        let mut hkey_classes = HKEY::default();
+        let classes_path = HSTRING::from(prefix.trim_end_matches('\\'));
+        let result = RegOpenKeyExW(root_hkey, &classes_path, 0, sam, &mut hkey_classes);
+        if result != ERROR_SUCCESS {
+            return Err(anyhow::anyhow!(
+                "Failed to open the classes root: error code {}",
+                result.0
+            ));
+        }
+
+        let mut classes = enum_subkey_names(hkey_classes);
+        classes.extend(NESTED_SHELLEX_CLASSES.iter().map(|class| class.to_string()));
+        for class in classes {
+            let shellex_path = format!("{class}\\shellex");
+            let Some(hkey_shellex) = open_key(hkey_classes, &shellex_path, KEY_READ, stats) else {
+                continue;
+            };
+            let handlers = read_shellex_handlers(hkey_shellex, &mut interface_names, stats);
+            for (clsid, handler_type) in handlers {
+                push(clsid, handler_type);
+            }
+            let _ = RegCloseKey(hkey_shellex);
+        }
+        let _ = RegCloseKey(hkey_classes);
+    }
+
+    Ok(extensions)
+}
+
+/// Indexes every ProgID below the classes root by the CLSID its `CLSID` subkey points to,
+/// as `(ProgID, CLSID)` pairs with the CLSID upper-case. File extensions (`.txt`) are skipped.
+pub fn read_prog_id_index(
+    root: RegistryRoot,
+    view_flag: REG_SAM_FLAGS,
+    stats: &mut ScanStats,
+) -> Result<Vec<(String, String)>> {
+    let mut index = Vec::new();
+    unsafe {
+        let (root_hkey, prefix) = classes_root_key(root);
+        let mut hkey_classes = HKEY::default();
+        let classes_path = HSTRING::from(prefix.trim_end_matches('\\'));
+        let sam = KEY_READ | view_flag;
+        let result = RegOpenKeyExW(root_hkey, &classes_path, 0, sam, &mut hkey_classes);
+        if result != ERROR_SUCCESS {
+            return Err(anyhow::anyhow!(
+                "Failed to open the classes root: error code {}",
+                result.0
+            ));
+        }
+
+        for name in enum_subkey_names(hkey_classes) {
+            if name.starts_with('.') {
+                continue;
+            }
+            let clsid_path = format!("{name}\\CLSID");
+            let Some(hkey_clsid) = open_key(hkey_classes, &clsid_path, KEY_READ, stats) else {
+                continue;
+            };
+            let clsid = read_registry_string(hkey_clsid, None);
+            let _ = RegCloseKey(hkey_clsid);
+            if let Some(clsid) = clsid.as_deref().and_then(as_clsid) {
+                index.push((name, clsid));
+            }
+        }
+        let _ = RegCloseKey(hkey_classes);
+    }
+    Ok(index)
+}
+
+/// Reads the handlers below an open `shellex` key as `(clsid, handler type)` pairs.
+/// A handler list such as `ContextMenuHandlers` holds one subkey per handler, named by its
+/// CLSID or holding it as the default value; an IID subkey holds the CLSID as its default value.
+fn read_shellex_handlers(
+    hkey_shellex: HKEY,
+    interface_names: &mut IidResolver,
+    stats: &mut ScanStats,
+) -> Vec<(String, String)> {
+    let mut handlers = Vec::new();
+    for subkey in enum_subkey_names(hkey_shellex) {
+        if as_clsid(&subkey).is_some() {
+            let clsid = read_subkey_default(hkey_shellex, &subkey);
+            if let Some(clsid) = clsid.as_deref().and_then(as_clsid) {
+                handlers.push((clsid, handler_type_name(&subkey, interface_names)));
+            }
+            continue;
+        }
+
+        let Some(hkey_list) = open_key(hkey_shellex, &subkey, KEY_READ, stats) else {
+            continue;
+        };
+        for entry in enum_subkey_names(hkey_list) {
+            let clsid = as_clsid(&entry)
+                .or_else(|| read_subkey_default(hkey_list, &entry).as_deref().and_then(as_clsid));
+            if let Some(clsid) = clsid {
+                handlers.push((clsid, subkey.clone()));
+            }
+        }
+        unsafe {
+            let _ = RegCloseKey(hkey_list);
+        }
+    }
+    handlers
+}
+
+/// Normalizes a registry name or value to an upper-case `{CLSID}`, or `None` if it isn't a
+/// braced GUID (disabled handlers are often renamed or set to `--`)
+fn as_clsid(value: &str) -> Option<String> {
+    let value = value.trim();
+    let inner = value.strip_prefix('{')?.strip_suffix('}')?;
+    let is_guid = inner.len() == 36
+        && inner.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        });
+    is_guid.then(|| value.to_uppercase())
+}
+
+/// Sets `writable_by_users` on every object whose CLSID key, in any of the given views, has a
+/// DACL that lets standard users write to it
+pub fn check_key_acls(
+    objects: &mut HashMap<String, ComObject>,
+    root: RegistryRoot,
+    views: &[REG_SAM_FLAGS],
+    stats: &mut ScanStats,
+) -> Result<()> {
+    for view_flag in views {
+        let hkey_clsid = open_clsid_key(root, *view_flag)?;
+        for obj in objects.values_mut() {
+            if !obj.writable_by_users {
+                obj.writable_by_users = key_writable_by_users(hkey_clsid, &obj.clsid, stats);
+            }
+        }
+        unsafe {
+            let _ = RegCloseKey(hkey_clsid);
+        }
+    }
+    Ok(())
+}
+
+/// Fills `extra` on every object with the named values of its CLSID key. A value found in
+/// an earlier view is kept; names are matched case-insensitively, as the registry does.
+pub fn read_extra_values(
+    objects: &mut HashMap<String, ComObject>,
+    root: RegistryRoot,
+    views: &[REG_SAM_FLAGS],
+    names: &[String],
+    stats: &mut ScanStats,
+) -> Result<()> {
+    for view_flag in views {
+        let hkey_clsid = open_clsid_key(root, *view_flag)?;
+        for obj in objects.values_mut() {
+            let Some(hkey_obj) = open_key(hkey_clsid, &obj.clsid, KEY_READ, stats) else {
+                continue;
+            };
+            for name in names {
+                if obj.extra.contains_key(name) {
+                    continue;
+                }
+                if let Some(value) = read_registry_string(hkey_obj, Some(name)) {
+                    obj.extra.insert(name.clone(), value);
+                }
+            }
+            unsafe {
+                let _ = RegCloseKey(hkey_obj);
+            }
+        }
+        unsafe {
+            let _ = RegCloseKey(hkey_clsid);
+        }
+    }
+    Ok(())
+}
+
+/// Fills `treat_as_target` on every object with a `TreatAs` value by following the chain of
+/// redirections in each view until a CLSID without one. A CLSID seen before in the chain ends
+/// it, so redirect cycles terminate. A target found in an earlier view is kept. Returns how many
+/// chains were cut by a cycle.
+pub fn resolve_treat_as(
+    objects: &mut HashMap<String, ComObject>,
+    root: RegistryRoot,
+    views: &[REG_SAM_FLAGS],
+    stats: &mut ScanStats,
+) -> Result<usize> {
+    let mut cycles = 0;
+    for view_flag in views {
+        let hkey_clsid = open_clsid_key(root, *view_flag)?;
+        for obj in objects.values_mut() {
+            if obj.treat_as_target.is_some() {
+                continue;
+            }
+            let Some(ref treat_as) = obj.treat_as else {
+                continue;
+            };
+            let target = follow_treat_as(hkey_clsid, &obj.clsid, treat_as, stats);
+            if target.as_ref().is_some_and(|target| target.cycle) {
+                cycles += 1;
+            }
+            obj.treat_as_target = target;
+        }
+        unsafe {
+            let _ = RegCloseKey(hkey_clsid);
+        }
+    }
+    Ok(cycles)
+}
+
+/// Follows `TreatAs` from `clsid` to the last class of the chain that is registered in this
+/// view; `None` if the first target is not registered
+fn follow_treat_as(
+    hkey_clsid: HKEY,
+    clsid: &str,
+    treat_as: &str,
+    stats: &mut ScanStats,
+) -> Option<TreatAsTarget> {
+    let mut visited = HashSet::from([normalize_clsid(clsid)]);
+    let mut next = treat_as.to_string();
+    let mut target: Option<TreatAsTarget> = None;
+    loop {
+        if !visited.insert(normalize_clsid(&next)) {
+            if let Some(ref mut target) = target {
+                target.cycle = true;
+            }
+            return target;
+        }
+        let key_name = format!("{{{}}}", normalize_clsid(&next).to_uppercase());
+        let Some(hkey_target) = open_key(hkey_clsid, &key_name, KEY_READ, stats) else {
+            return target;
+        };
+        let (prog_id, description) = get_prog_id_and_description(hkey_target, stats);
+        let (inproc_server, _) = get_inproc_server(hkey_target, stats);
+        let local_server = get_server_path(hkey_target, "LocalServer32", stats);
+        let treat_as = get_treat_as(hkey_target);
+        unsafe {
+            let _ = RegCloseKey(hkey_target);
+        }
+
+        target = Some(TreatAsTarget {
+            clsid: key_name,
+            prog_id: split_typed(prog_id).0,
+            description: split_typed(description).0,
+            inproc_server: split_typed(inproc_server).0,
+            local_server: split_typed(local_server).0,
+            hops: target.map_or(1, |previous| previous.hops + 1),
+            cycle: false,
+        });
+        match treat_as {
+            Some(treat_as) => next = treat_as,
+            None => return target,
+        }
+    }
+}
+
+/// Retrieves the CLSID named by the `TreatAs` subkey of an open CLSID key
+fn get_treat_as(hkey_obj: HKEY) -> Option<String> {
+    read_subkey_default(hkey_obj, "TreatAs").filter(|v| !v.trim().is_empty())
+}
+
+/// Reads the DACL of one CLSID key and checks it for write access by standard users.
+/// A key that cannot be opened or read is reported as not writable.
+fn key_writable_by_users(hkey_clsid: HKEY, clsid: &str, stats: &mut ScanStats) -> bool {
+    let Some(hkey_obj) = open_key(hkey_clsid, clsid, KEY_READ, stats) else {
+        return false;
+    };
+    unsafe {
+        let mut size = 0u32;
+        let _ = RegGetKeySecurity(
+            hkey_obj,
+            DACL_SECURITY_INFORMATION,
+            PSECURITY_DESCRIPTOR::default(),
+            &mut size,
+        );
+        let mut buffer = vec![0u8; size as usize];
+        let descriptor = PSECURITY_DESCRIPTOR(buffer.as_mut_ptr() as *mut _);
+        let result = RegGetKeySecurity(hkey_obj, DACL_SECURITY_INFORMATION, descriptor, &mut size);
+        let _ = RegCloseKey(hkey_obj);
+
+        if result == ERROR_ACCESS_DENIED {
+            stats.access_denied += 1;
+        }
+        result == ERROR_SUCCESS
+            && grants_write_to_users(descriptor, KEY_WRITE_RIGHTS).unwrap_or(false)
+    }
+}
+
+/// Opens `{root}\CLSID` in the given registry view. The caller must close the key.
+fn open_clsid_key(root: RegistryRoot, view_flag: REG_SAM_FLAGS) -> Result<HKEY> {
+    unsafe {
+        let (root_hkey, prefix) = classes_root_key(root);
+        let clsid_path = HSTRING::from(format!("{prefix}CLSID"));
+        let mut hkey_clsid = HKEY::default();
+
+        // Open {root}\CLSID with specified view
+        let result = RegOpenKeyExW(
+            root_hkey,
+            &clsid_path,
+            0,
+            KEY_READ | view_flag,
+            &mut hkey_clsid,
+        );
+
+        if result != ERROR_SUCCESS {
+            return Err(anyhow::anyhow!(
+                "Failed to open CLSID key: error code {}",
+                result.0
+            ));
+        }
+
+        Ok(hkey_clsid)
+    }
+}
+
+/// Reads every field the scanner gathers for one CLSID below an open `CLSID` key. The scope
+/// costs two extra key opens, so it is only read with `read_scope`; scans read it for their
+/// results afterwards with [`read_registration_scopes`].
+fn read_com_object(
+    root: RegistryRoot,
+    view_flag: REG_SAM_FLAGS,
+    hkey_clsid: HKEY,
+    clsid: String,
+    read_scope: bool,
+    caches: &mut LookupCaches,
+    stats: &mut ScanStats,
+) -> ComObject {
+    // Determine per-user vs machine-wide registration
+    let scope = read_scope.then(|| registration_scope(view_flag, &clsid, stats)).flatten();
+
+    // Every other field is read through the CLSID's own key, opened once and used as the
+    // parent of its subkeys
+    let Some(hkey_obj) = open_key(hkey_clsid, &clsid, KEY_READ, stats) else {
+        return ComObject {
+            scope,
+            ..ComObject::new(clsid, root)
+        };
+    };
+
+    // ProgID and description (default value), read together from the open key
+    let (prog_id, description) = get_prog_id_and_description(hkey_obj, stats);
+    let (prog_id, prog_id_type) = split_typed(prog_id);
+    let (description, description_type) = split_typed(description);
 
-use crate::types::ComObject;
-use crate::filter::should_include_object;
+    // Follow CurVer to the current versioned ProgID
+    let resolved_prog_id = prog_id
+        .as_deref()
+        .and_then(|p| resolve_cur_ver(root, view_flag, p, stats));
 
-/// Scans the Windows registry for COM objects with specified filters
-pub fn scan_com_objects(
+    // DCOM application the class runs under
+    let app_id = get_app_id(hkey_obj);
+
+    // Type library describing the class's interfaces
+    let type_lib = read_subkey_default(hkey_obj, "TypeLib").filter(|v| !v.is_empty());
+
+    // Class COM creates in place of this one
+    let treat_as = get_treat_as(hkey_obj);
+
+    // Identity the DCOM application runs under, shared by every class of the AppID
+    let run_as = app_id.as_deref().and_then(|app_id| {
+        caches
+            .run_as
+            .entry(app_id.to_uppercase())
+            .or_insert_with(|| get_run_as(root, view_flag, app_id, stats))
+            .clone()
+    });
+
+    // Server registrations: the DLL or EXE that actually implements the class
+    let (inproc_server, threading_model) = get_inproc_server(hkey_obj, stats);
+    let (inproc_server, inproc_server_type) = split_typed(inproc_server);
+    let (local_server, local_server_type) =
+        split_typed(get_server_path(hkey_obj, "LocalServer32", stats));
+    let unquoted_path = local_server.as_deref().is_some_and(is_unquoted_with_spaces);
+
+    // Collect implemented component categories
+    let implemented_categories: Vec<ComponentCategory> = get_implemented_categories(hkey_obj, stats)
+        .into_iter()
+        .map(|catid| {
+            let name = caches
+                .category_names
+                .entry(catid.to_uppercase())
+                .or_insert_with(|| resolve_category_name(&catid, stats))
+                .clone();
+            ComponentCategory { catid, name }
+        })
+        .collect();
+    let safe_for_scripting = is_safe_for_scripting(&implemented_categories);
+
+    // ActiveX controls are marked with a `Control` subkey
+    let is_control = has_subkey(hkey_obj, "Control", stats);
+
+    // Embeddable OLE documents are marked with an `Insertable` subkey
+    let is_insertable = has_subkey(hkey_obj, "Insertable", stats);
+
+    // Handler types the class registers under its own `shellex` subkey
+    let shellex = get_shellex_handlers(hkey_obj, &mut caches.interface_names, stats);
+
+    // When the CLSID key was last written (e.g. by an installer)
+    let last_write_time = get_last_write_time(hkey_obj);
+
+    unsafe {
+        let _ = RegCloseKey(hkey_obj);
+    }
+
+    let obj = ComObject {
+        scope,
+        prog_id,
+        resolved_prog_id,
+        description,
+        inproc_server,
+        threading_model,
+        local_server,
+        value_types: ValueTypes {
+            prog_id: prog_id_type,
+            description: description_type,
+            inproc_server: inproc_server_type,
+            local_server: local_server_type,
+        },
+        app_id,
+        type_lib,
+        treat_as,
+        run_as,
+        implemented_categories,
+        safe_for_scripting,
+        is_control,
+        is_insertable,
+        last_write_time,
+        unquoted_path,
+        shellex,
+        ..ComObject::new(clsid, root)
+    };
+    // Whether the server file exists decides whether the class can be used at all
+    ComObject {
+        server_missing: obj.has_missing_server(),
+        ..obj
+    }
+}
+
+/// Sets the scope of every object that has none yet, combining what each registry view
+/// registers under HKCU and HKLM classes. Runs after the scan on the objects that are
+/// listed, so the filtered-out CLSIDs never pay for the lookups.
+pub fn read_registration_scopes(
+    objects: &mut HashMap<String, ComObject>,
+    views: &[REG_SAM_FLAGS],
+    stats: &mut ScanStats,
+) {
+    for obj in objects.values_mut().filter(|obj| obj.scope.is_none()) {
+        obj.scope = views
+            .iter()
+            .filter_map(|view_flag| registration_scope(*view_flag, &obj.clsid, stats))
+            .reduce(RegistrationScope::combine);
+    }
+}
+
+/// Checks whether a CLSID is registered under HKCU classes, HKLM classes, or both
+fn registration_scope(
     view_flag: REG_SAM_FLAGS,
-    limit: usize,
-    filter_description: &Option<String>,
-    filter_clsid: &Option<String>,
-    filter_app: &Option<Vec<String>>,
-    interactive_filter: &Option<String>,
-) -> Result<HashMap<String, ComObject>> {
-    let mut objects = HashMap::new();
+    clsid: &str,
+    stats: &mut ScanStats,
+) -> Option<RegistrationScope> {
+    let mut in_root = |root: RegistryRoot| {
+        let (root_hkey, prefix) = classes_root_key(root);
+        let path = format!("{prefix}CLSID\\{clsid}");
+
+        match open_key(root_hkey, &path, KEY_READ | view_flag, stats) {
+            Some(hkey) => {
+                unsafe {
+                    let _ = RegCloseKey(hkey);
+                }
+                true
+            }
+            None => false,
+        }
+    };
+
+    let in_user = in_root(RegistryRoot::CurrentUser);
+    let in_machine = in_root(RegistryRoot::LocalMachine);
+    match (in_user, in_machine) {
+        (true, true) => Some(RegistrationScope::Both),
+        (true, false) => Some(RegistrationScope::User),
+        (false, true) => Some(RegistrationScope::Machine),
+        (false, false) => None,
+    }
+}
+
+/// Returns the predefined key and subkey prefix under which a classes root lives
+fn classes_root_key(root: RegistryRoot) -> (HKEY, &'static str) {
+    match root {
+        RegistryRoot::ClassesRoot => (HKEY_CLASSES_ROOT, ""),
+        RegistryRoot::LocalMachine => (HKEY_LOCAL_MACHINE, "SOFTWARE\\Classes\\"),
+        RegistryRoot::CurrentUser => (HKEY_CURRENT_USER, "Software\\Classes\\"),
+    }
+}
+
+/// Reads every interface registered under `{root}\Interface` in one registry view, with its
+/// name and proxy/stub registration. Stops early with the interfaces read so far once
+/// `cancel` is set.
+pub fn scan_interfaces(
+    root: RegistryRoot,
+    view_flag: REG_SAM_FLAGS,
+    cancel: &AtomicBool,
+    stats: &mut ScanStats,
+) -> Result<Vec<InterfaceInfo>> {
+    let mut interfaces = Vec::new();
 
     unsafe {
-        let clsid_path = HSTRING::from("CLSID");
-        let mut hkey_clsid = HKEY::default();
+        let (root_hkey, prefix) = classes_root_key(root);
+        let interface_path = HSTRING::from(format!("{prefix}Interface"));
+        let mut hkey_interface = HKEY::default();
 
-        // Open HKEY_CLASSES_ROOT\CLSID with specified view
         let result = RegOpenKeyExW(
-            HKEY_CLASSES_ROOT,
-            &clsid_path,
+            root_hkey,
+            &interface_path,
             0,
             KEY_READ | view_flag,
-            &mut hkey_clsid,
+            &mut hkey_interface,
+        );
+        if result != ERROR_SUCCESS {
+            return Err(anyhow::anyhow!(
+                "Failed to open Interface key: error code {}",
+                result.0
+            ));
+        }
+
+        for iid in enum_subkey_names(hkey_interface) {
+            if cancel.load(Ordering::Relaxed) {
+                break;
+            }
+            stats.enumerated += 1;
+            let Some(hkey_iid) = open_key(hkey_interface, &iid, KEY_READ, stats) else {
+                continue;
+            };
+            // Interfaces without a registered name may still be well-known system interfaces
+            let name = read_registry_string(hkey_iid, None)
+                .or_else(|| well_known_interface_name(&iid).map(str::to_string));
+            let proxy_stub_clsid = read_subkey_default(hkey_iid, "ProxyStubClsid32");
+            let type_lib = read_subkey_default(hkey_iid, "TypeLib");
+            let _ = RegCloseKey(hkey_iid);
+
+            interfaces.push(InterfaceInfo {
+                iid,
+                name,
+                proxy_stub_clsid,
+                type_lib,
+            });
+        }
+
+        let _ = RegCloseKey(hkey_interface);
+    }
+
+    Ok(interfaces)
+}
+
+/// Reads the name of an interface from `HKEY_CLASSES_ROOT\Interface\{iid}` (braces optional)
+pub fn read_interface_name(iid: &str) -> Option<String> {
+    let iid = format!("{{{}}}", normalize_clsid(iid).to_uppercase());
+    read_subkey_default(HKEY_CLASSES_ROOT, &format!("Interface\\{iid}"))
+}
+
+/// Reads every registered version of a type library from `HKEY_CLASSES_ROOT\TypeLib\{guid}`
+pub fn read_typelib_versions(guid: &str) -> Result<Vec<TypeLibVersion>> {
+    let guid = format!("{{{}}}", normalize_clsid(guid).to_uppercase());
+    let mut versions = Vec::new();
+
+    unsafe {
+        let typelib_path = HSTRING::from(format!("TypeLib\\{guid}"));
+        let mut hkey_typelib = HKEY::default();
+
+        let result = RegOpenKeyExW(
+            HKEY_CLASSES_ROOT,
+            &typelib_path,
+            0,
+            KEY_READ,
+            &mut hkey_typelib,
         );
 
         if result != ERROR_SUCCESS {
             return Err(anyhow::anyhow!(
-                "Failed to open CLSID key: error code {}",
+                "Failed to open TypeLib key for {guid}: error code {}",
                 result.0
             ));
         }
 
-        // Enumerate all CLSIDs
+        for version in enum_subkey_names(hkey_typelib) {
+            let mut hkey_version = HKEY::default();
+            let version_path = HSTRING::from(version.as_str());
+            if RegOpenKeyExW(hkey_typelib, &version_path, 0, KEY_READ, &mut hkey_version)
+                != ERROR_SUCCESS
+            {
+                continue;
+            }
+
+            let description = read_registry_string(hkey_version, None);
+            let mut flags = None;
+            let mut platforms = Vec::new();
+
+            for subkey in enum_subkey_names(hkey_version) {
+                if subkey.eq_ignore_ascii_case("FLAGS") {
+                    flags = read_subkey_default(hkey_version, &subkey);
+                } else if subkey.eq_ignore_ascii_case("HELPDIR") {
+                    continue;
+                } else {
+                    // Remaining subkeys are LCIDs, each holding one subkey per platform
+                    let mut hkey_lcid = HKEY::default();
+                    let lcid_path = HSTRING::from(subkey.as_str());
+                    if RegOpenKeyExW(hkey_version, &lcid_path, 0, KEY_READ, &mut hkey_lcid)
+                        == ERROR_SUCCESS
+                    {
+                        for platform in enum_subkey_names(hkey_lcid) {
+                            let path = read_subkey_default(hkey_lcid, &platform);
+                            platforms.push(TypeLibPlatform {
+                                lcid: subkey.clone(),
+                                platform,
+                                path,
+                            });
+                        }
+                        let _ = RegCloseKey(hkey_lcid);
+                    }
+                }
+            }
+
+            let _ = RegCloseKey(hkey_version);
+            versions.push(TypeLibVersion {
+                version,
+                description,
+                flags,
+                platforms,
+            });
+        }
+
+        let _ = RegCloseKey(hkey_typelib);
+    }
+
+    Ok(versions)
+}
+
+/// Reads the default value of a direct subkey
+fn read_subkey_default(hkey: HKEY, subkey: &str) -> Option<String> {
+    unsafe {
+        let subkey_path = HSTRING::from(subkey);
+        let mut hkey_sub = HKEY::default();
+
+        if RegOpenKeyExW(hkey, &subkey_path, 0, KEY_READ, &mut hkey_sub) == ERROR_SUCCESS {
+            let value = read_registry_string(hkey_sub, None);
+            let _ = RegCloseKey(hkey_sub);
+            return value;
+        }
+    }
+    None
+}
+
+/// Outcome of reading a string value: a value that is not set is told apart from one that
+/// exists but could not be read because access was denied
+enum ValueRead {
+    /// The string and the registry type it is stored as (`REG_SZ`, `REG_EXPAND_SZ`, ...)
+    Present(String, u32),
+    Absent,
+    Denied,
+}
+
+/// A string value with its registry type, as [`read_registry_string_typed`] returns it
+type TypedString = (String, u32);
+
+impl ValueRead {
+    /// The value, if it was read. A denied read is counted in `stats.values_denied`.
+    fn into_value(self, stats: &mut ScanStats) -> Option<TypedString> {
+        match self {
+            ValueRead::Present(value, value_type) => Some((value, value_type)),
+            ValueRead::Absent => None,
+            ValueRead::Denied => {
+                stats.values_denied += 1;
+                None
+            }
+        }
+    }
+}
+
+/// Retrieves the ProgID (default value of the `ProgID` subkey) and the description (default
+/// value) of an open CLSID key, opening only the `ProgID` subkey. Values that access was
+/// denied to are counted in `stats.values_denied`.
+fn get_prog_id_and_description(
+    hkey_obj: HKEY,
+    stats: &mut ScanStats,
+) -> (Option<TypedString>, Option<TypedString>) {
+    let description = query_registry_string(hkey_obj, None).into_value(stats);
+    let prog_id = read_subkey_value(hkey_obj, "ProgID", stats).into_value(stats);
+    (prog_id, description)
+}
+
+/// Reads the default value of a direct subkey, telling a missing subkey or value apart from
+/// one that access was denied to. Other open failures count in `stats.open_errors`.
+fn read_subkey_value(hkey: HKEY, subkey: &str, stats: &mut ScanStats) -> ValueRead {
+    unsafe {
+        let subkey_path = HSTRING::from(subkey);
+        let mut hkey_sub = HKEY::default();
+
+        let result = RegOpenKeyExW(hkey, &subkey_path, 0, KEY_READ, &mut hkey_sub);
+        if result == ERROR_ACCESS_DENIED {
+            return ValueRead::Denied;
+        }
+        if result != ERROR_SUCCESS {
+            if result != ERROR_FILE_NOT_FOUND {
+                stats.open_errors += 1;
+            }
+            return ValueRead::Absent;
+        }
+        let value = query_registry_string(hkey_sub, None);
+        let _ = RegCloseKey(hkey_sub);
+        value
+    }
+}
+
+/// Follows the `CurVer` chain of a ProgID to its current versioned ProgID.
+/// Returns `None` if the ProgID has no `CurVer` or it points back to itself.
+fn resolve_cur_ver(
+    root: RegistryRoot,
+    view_flag: REG_SAM_FLAGS,
+    prog_id: &str,
+    stats: &mut ScanStats,
+) -> Option<String> {
+    // Track visited ProgIDs so a CurVer cycle cannot loop forever
+    let mut visited = vec![prog_id.to_lowercase()];
+    let mut current = prog_id.to_string();
+
+    while let Some(next) = read_cur_ver(root, view_flag, &current, stats) {
+        let next_lower = next.to_lowercase();
+        if visited.contains(&next_lower) {
+            break;
+        }
+        visited.push(next_lower);
+        current = next;
+    }
+
+    if current.eq_ignore_ascii_case(prog_id) {
+        None
+    } else {
+        Some(current)
+    }
+}
+
+/// Reads the default value of `{root}\{prog_id}\CurVer`
+fn read_cur_ver(
+    root: RegistryRoot,
+    view_flag: REG_SAM_FLAGS,
+    prog_id: &str,
+    stats: &mut ScanStats,
+) -> Option<String> {
+    let (root_hkey, prefix) = classes_root_key(root);
+    let curver_path = format!("{prefix}{prog_id}\\CurVer");
+    let hkey_curver = open_key(root_hkey, &curver_path, KEY_READ | view_flag, stats)?;
+    unsafe {
+        let value = read_registry_string(hkey_curver, None);
+        let _ = RegCloseKey(hkey_curver);
+        value.filter(|v| !v.is_empty())
+    }
+}
+
+/// Retrieves the `AppID` value of an open CLSID key, linking it to its DCOM application
+fn get_app_id(hkey_obj: HKEY) -> Option<String> {
+    read_registry_string(hkey_obj, Some("AppID")).filter(|v| !v.is_empty())
+}
+
+/// Retrieves the `RunAs` value of an AppID key: the account its server is launched as, such as
+/// `Interactive User` or a named user
+fn get_run_as(
+    root: RegistryRoot,
+    view_flag: REG_SAM_FLAGS,
+    app_id: &str,
+    stats: &mut ScanStats,
+) -> Option<String> {
+    let (root_hkey, prefix) = classes_root_key(root);
+    let app_id = format!("{{{}}}", normalize_clsid(app_id).to_uppercase());
+    let path = format!("{prefix}AppID\\{app_id}");
+    let hkey_app_id = open_key(root_hkey, &path, KEY_READ | view_flag, stats)?;
+    unsafe {
+        let value = read_registry_string(hkey_app_id, Some("RunAs"));
+        let _ = RegCloseKey(hkey_app_id);
+        value.filter(|v| !v.is_empty())
+    }
+}
+
+/// Retrieves the last write time of an open CLSID key
+fn get_last_write_time(hkey_obj: HKEY) -> Option<DateTime<Utc>> {
+    unsafe {
+        let mut filetime = FILETIME::default();
+        let result = RegQueryInfoKeyW(
+            hkey_obj,
+            PWSTR::null(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(&mut filetime),
+        );
+        if result != ERROR_SUCCESS {
+            return None;
+        }
+        filetime_to_utc(filetime)
+    }
+}
+
+/// Converts a `FILETIME` (100 ns intervals since 1601-01-01 UTC) to a UTC timestamp
+fn filetime_to_utc(filetime: FILETIME) -> Option<DateTime<Utc>> {
+    const INTERVALS_PER_SECOND: u64 = 10_000_000;
+    const UNIX_EPOCH_OFFSET_SECS: i64 = 11_644_473_600;
+
+    let intervals = (u64::from(filetime.dwHighDateTime) << 32) | u64::from(filetime.dwLowDateTime);
+    let secs = (intervals / INTERVALS_PER_SECOND) as i64 - UNIX_EPOCH_OFFSET_SECS;
+    let nanos = (intervals % INTERVALS_PER_SECOND) as u32 * 100;
+    DateTime::from_timestamp(secs, nanos)
+}
+
+/// Retrieves the default value of a server subkey (`InprocServer32`, `LocalServer32`) of an
+/// open CLSID key
+fn get_server_path(hkey_obj: HKEY, server: &str, stats: &mut ScanStats) -> Option<TypedString> {
+    let hkey_server = open_key(hkey_obj, server, KEY_READ, stats)?;
+    unsafe {
+        let value = read_registry_string_typed(hkey_server, None);
+        let _ = RegCloseKey(hkey_server);
+        value.filter(|(v, _)| !v.is_empty())
+    }
+}
+
+/// Retrieves the DLL and the `ThreadingModel` of the `InprocServer32` subkey of an open CLSID
+/// key, read together from the open subkey
+fn get_inproc_server(
+    hkey_obj: HKEY,
+    stats: &mut ScanStats,
+) -> (Option<TypedString>, Option<String>) {
+    let Some(hkey_server) = open_key(hkey_obj, "InprocServer32", KEY_READ, stats) else {
+        return (None, None);
+    };
+    unsafe {
+        let server = read_registry_string_typed(hkey_server, None).filter(|(v, _)| !v.is_empty());
+        let threading_model =
+            read_registry_string(hkey_server, Some("ThreadingModel")).filter(|v| !v.is_empty());
+        let _ = RegCloseKey(hkey_server);
+        (server, threading_model)
+    }
+}
+
+/// Retrieves the category GUIDs listed under the `Implemented Categories` subkey of an open
+/// CLSID key
+fn get_implemented_categories(hkey_obj: HKEY, stats: &mut ScanStats) -> Vec<String> {
+    let Some(hkey_categories) = open_key(hkey_obj, "Implemented Categories", KEY_READ, stats)
+    else {
+        return Vec::new();
+    };
+    unsafe {
+        let categories = enum_subkey_names(hkey_categories);
+        let _ = RegCloseKey(hkey_categories);
+        categories
+    }
+}
+
+/// Lists the handler-type subkeys below the `shellex` subkey of an open CLSID key, naming
+/// well-known interface IIDs
+fn get_shellex_handlers(
+    hkey_obj: HKEY,
+    interface_names: &mut IidResolver,
+    stats: &mut ScanStats,
+) -> Vec<String> {
+    let Some(hkey_shellex) = open_key(hkey_obj, "shellex", KEY_READ, stats) else {
+        return Vec::new();
+    };
+    unsafe {
+        let handlers = enum_subkey_names(hkey_shellex);
+        let _ = RegCloseKey(hkey_shellex);
+        handlers.iter().map(|handler| handler_type_name(handler, interface_names)).collect()
+    }
+}
+
+/// Resolves a category GUID to a name: well-known categories first, then the localized
+/// description under `HKEY_CLASSES_ROOT\Component Categories\{catid}`
+fn resolve_category_name(catid: &str, stats: &mut ScanStats) -> Option<String> {
+    if let Some(name) = well_known_category_name(catid) {
+        return Some(name.to_string());
+    }
+
+    let category_path = format!("Component Categories\\{catid}");
+    let hkey_category = open_key(HKEY_CLASSES_ROOT, &category_path, KEY_READ, stats)?;
+    unsafe {
+        // Descriptions are stored as values named by hex LCID; prefer English (409)
+        let name = read_registry_string(hkey_category, Some("409")).or_else(|| {
+            enum_value_names(hkey_category)
+                .into_iter()
+                .find_map(|value_name| read_registry_string(hkey_category, Some(&value_name)))
+        });
+        let _ = RegCloseKey(hkey_category);
+        name.filter(|n| !n.is_empty())
+    }
+}
+
+/// Lists the names of all values of an open registry key
+fn enum_value_names(hkey: HKEY) -> Vec<String> {
+    let mut names = Vec::new();
+
+    unsafe {
         let mut index = 0u32;
         loop {
-            let mut name_buffer = [0u16; 256];
+            let mut name_buffer = [0u16; 16384];
             let mut name_len = name_buffer.len() as u32;
 
-            let result = RegEnumKeyExW(
-                hkey_clsid,
+            let result = RegEnumValueW(
+                hkey,
                 index,
                 PWSTR(name_buffer.as_mut_ptr()),
                 &mut name_len,
                 None,
-                PWSTR::null(),
+                None,
                 None,
                 None,
             );
@@ -67,124 +1530,231 @@ pub fn scan_com_objects(
                 break;
             }
 
-            let clsid = String::from_utf16_lossy(&name_buffer[..name_len as usize]);
-
-            // Try to get ProgID for this CLSID
-            let prog_id = get_prog_id(hkey_clsid, &clsid);
-
-            // Try to get description (default value)
-            let description = get_description(hkey_clsid, &clsid);
-
-            // Check if this object passes all filters
-            if should_include_object(
-                &prog_id,
-                &description,
-                &clsid,
-                interactive_filter,
-                filter_description,
-                filter_clsid,
-                filter_app,
-            ) {
-                objects.insert(
-                    clsid.clone(),
-                    ComObject {
-                        clsid,
-                        prog_id,
-                        description,
-                    },
-                );
-
-                // Check limit
-                if limit > 0 && objects.len() >= limit {
-                    break;
-                }
-            }
-
+            names.push(String::from_utf16_lossy(&name_buffer[..name_len as usize]));
             index += 1;
         }
-
-        let _ = RegCloseKey(hkey_clsid);
     }
 
-    Ok(objects)
+    names
+}
+
+/// Checks whether a subkey exists (and can be opened) below the given key
+fn has_subkey(hkey: HKEY, path: &str, stats: &mut ScanStats) -> bool {
+    match open_key(hkey, path, KEY_READ, stats) {
+        Some(hkey_sub) => {
+            unsafe {
+                let _ = RegCloseKey(hkey_sub);
+            }
+            true
+        }
+        None => false,
+    }
 }
 
-/// Retrieves the ProgID for a given CLSID from the registry
-fn get_prog_id(hkey_clsid: HKEY, clsid: &str) -> Option<String> {
+/// Opens a subkey for a per-CLSID read. A missing key is expected and not counted;
+/// access-denied and other failures are tallied in the scan stats.
+/// The caller must close the returned key.
+fn open_key(parent: HKEY, path: &str, sam: REG_SAM_FLAGS, stats: &mut ScanStats) -> Option<HKEY> {
     unsafe {
-        let progid_path = HSTRING::from(format!("{clsid}\\ProgID"));
-        let mut hkey_progid = HKEY::default();
+        let subkey_path = HSTRING::from(path);
+        let mut hkey = HKEY::default();
 
-        if RegOpenKeyExW(hkey_clsid, &progid_path, 0, KEY_READ, &mut hkey_progid) == ERROR_SUCCESS
-        {
-            let value = read_registry_string(hkey_progid, None);
-            let _ = RegCloseKey(hkey_progid);
-            return value;
+        let result = RegOpenKeyExW(parent, &subkey_path, 0, sam, &mut hkey);
+        if result == ERROR_SUCCESS {
+            return Some(hkey);
+        }
+
+        if result == ERROR_ACCESS_DENIED {
+            stats.access_denied += 1;
+        } else if result != ERROR_FILE_NOT_FOUND {
+            stats.open_errors += 1;
         }
     }
     None
 }
 
-/// Retrieves the description for a given CLSID from the registry
-fn get_description(hkey_clsid: HKEY, clsid: &str) -> Option<String> {
+/// Lists the names of all direct subkeys of an open registry key
+fn enum_subkey_names(hkey: HKEY) -> Vec<String> {
+    let mut names = Vec::new();
+
     unsafe {
-        let clsid_path = HSTRING::from(clsid);
-        let mut hkey_obj = HKEY::default();
+        let mut index = 0u32;
+        loop {
+            let mut name_buffer = [0u16; 256];
+            let mut name_len = name_buffer.len() as u32;
 
-        if RegOpenKeyExW(hkey_clsid, &clsid_path, 0, KEY_READ, &mut hkey_obj) == ERROR_SUCCESS {
-            let value = read_registry_string(hkey_obj, None);
-            let _ = RegCloseKey(hkey_obj);
-            return value;
+            let result = RegEnumKeyExW(
+                hkey,
+                index,
+                PWSTR(name_buffer.as_mut_ptr()),
+                &mut name_len,
+                None,
+                PWSTR::null(),
+                None,
+                None,
+            );
+
+            if result != ERROR_SUCCESS {
+                break;
+            }
+
+            names.push(String::from_utf16_lossy(&name_buffer[..name_len as usize]));
+            index += 1;
         }
     }
-    None
+
+    names
 }
 
 /// Low-level registry value reading with UTF-16 to UTF-8 conversion
 fn read_registry_string(hkey: HKEY, value_name: Option<&str>) -> Option<String> {
+    read_registry_string_typed(hkey, value_name).map(|(value, _)| value)
+}
+
+/// Reads a string value like [`read_registry_string`], together with the registry type it is
+/// stored as
+fn read_registry_string_typed(hkey: HKEY, value_name: Option<&str>) -> Option<TypedString> {
+    match query_registry_string(hkey, value_name) {
+        ValueRead::Present(value, value_type) => Some((value, value_type)),
+        ValueRead::Absent | ValueRead::Denied => None,
+    }
+}
+
+/// Split a typed value into the value and its type, for the [`ValueTypes`] of an object
+fn split_typed(value: Option<TypedString>) -> (Option<String>, Option<u32>) {
+    value.map_or((None, None), |(value, value_type)| (Some(value), Some(value_type)))
+}
+
+/// Reads a string value like [`read_registry_string`], reporting whether a value that could
+/// not be read is missing or denied
+fn query_registry_string(hkey: HKEY, value_name: Option<&str>) -> ValueRead {
+    VALUE_BUFFER.with(|buffer| read_registry_string_into(hkey, value_name, &mut buffer.borrow_mut()))
+}
+
+/// Reads a string value into `buffer`, growing it only when the value does not fit.
+/// Most values fit the buffer, so they are read with a single query and no allocation
+/// besides the returned string.
+fn read_registry_string_into(
+    hkey: HKEY,
+    value_name: Option<&str>,
+    buffer: &mut Vec<u16>,
+) -> ValueRead {
     unsafe {
-        let value_pcwstr = match value_name {
-            Some(name) => {
-                let hstring = HSTRING::from(name);
-                PCWSTR(hstring.as_ptr())
-            }
+        // Keep the HSTRING alive for as long as the pointer is used
+        let value_hstring = value_name.map(HSTRING::from);
+        let value_pcwstr = match value_hstring {
+            Some(ref hstring) => PCWSTR(hstring.as_ptr()),
             None => PCWSTR::null(),
         };
 
-        let mut size = 0u32;
-        let mut type_code = REG_VALUE_TYPE(0);
+        let mut value_type = REG_VALUE_TYPE::default();
+        loop {
+            let mut size = (buffer.len() * 2) as u32;
+            let result = RegQueryValueExW(
+                hkey,
+                value_pcwstr,
+                None,
+                Some(&mut value_type),
+                Some(buffer.as_mut_ptr() as *mut u8),
+                Some(&mut size),
+            );
 
-        // Get size
-        let result = RegQueryValueExW(
-            hkey,
-            value_pcwstr,
-            None,
-            Some(&mut type_code),
-            None,
-            Some(&mut size),
-        );
+            // `size` now holds the required size in bytes; grow and read again
+            if result == ERROR_MORE_DATA {
+                buffer.resize((size as usize).div_ceil(2), 0);
+                continue;
+            }
+            if result == ERROR_ACCESS_DENIED {
+                return ValueRead::Denied;
+            }
+            if result != ERROR_SUCCESS || size == 0 {
+                return ValueRead::Absent;
+            }
 
-        if result != ERROR_SUCCESS || size == 0 {
-            return None;
+            return ValueRead::Present(decode_registry_string(buffer, size as usize), value_type.0);
         }
+    }
+}
 
-        // Read value
-        let mut buffer = vec![0u16; (size / 2) as usize];
-        let result = RegQueryValueExW(
-            hkey,
-            value_pcwstr,
-            None,
-            Some(&mut type_code),
-            Some(buffer.as_mut_ptr() as *mut u8),
-            Some(&mut size),
-        );
+/// Decode the first `size` bytes of a registry value read into `buffer`.
+///
+/// A trailing odd byte is dropped and the text stops at the first NUL, so a
+/// missing terminator is tolerated and a `REG_MULTI_SZ` list yields its first
+/// string.
+fn decode_registry_string(buffer: &[u16], size: usize) -> String {
+    let value = &buffer[..(size / 2).min(buffer.len())];
+    let len = value.iter().position(|&c| c == 0).unwrap_or(value.len());
+    String::from_utf16_lossy(&value[..len])
+}
 
-        if result != ERROR_SUCCESS {
-            return None;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // Remove null terminator and convert
+    /// The decode of the old allocation-per-read path: a fresh buffer sized
+    /// from the reported byte count, trimmed at the first NUL.
+    fn old_decode(bytes: &[u8]) -> String {
+        let mut buffer = vec![0u16; bytes.len() / 2];
+        for (word, pair) in buffer.iter_mut().zip(bytes.chunks_exact(2)) {
+            *word = u16::from_le_bytes([pair[0], pair[1]]);
+        }
         let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
-        Some(String::from_utf16_lossy(&buffer[..len]))
+        String::from_utf16_lossy(&buffer[..len])
+    }
+
+    /// The decode of the reused buffer, grown the way `read_registry_string_into`
+    /// grows it and holding stale data from a longer earlier read.
+    fn new_decode(bytes: &[u8]) -> String {
+        let mut buffer = vec![0xFFFFu16; bytes.len().div_ceil(2) + 4];
+        let mut raw = bytes.to_vec();
+        raw.resize(buffer.len() * 2, 0xFF);
+        for (word, pair) in buffer.iter_mut().zip(raw.chunks_exact(2)) {
+            *word = u16::from_le_bytes([pair[0], pair[1]]);
+        }
+        decode_registry_string(&buffer, bytes.len())
+    }
+
+    fn utf16_bytes(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    fn assert_same(bytes: &[u8], expected: &str) {
+        assert_eq!(old_decode(bytes), expected);
+        assert_eq!(new_decode(bytes), expected);
+    }
+
+    #[test]
+    fn terminated_string() {
+        assert_same(&utf16_bytes("Shell.Explorer.2\0"), "Shell.Explorer.2");
+    }
+
+    #[test]
+    fn missing_terminator() {
+        assert_same(&utf16_bytes("Apartment"), "Apartment");
+    }
+
+    #[test]
+    fn embedded_nul() {
+        assert_same(&utf16_bytes("first\0second\0"), "first");
+        assert_same(&utf16_bytes("\0hidden"), "");
+    }
+
+    #[test]
+    fn odd_byte_length() {
+        let mut bytes = utf16_bytes("odd");
+        bytes.push(b'x');
+        assert_same(&bytes, "odd");
+        assert_same(b"x", "");
+    }
+
+    #[test]
+    fn multi_string_yields_first() {
+        assert_same(&utf16_bytes("one\0two\0three\0\0"), "one");
+    }
+
+    #[test]
+    fn empty_and_non_ascii() {
+        assert_same(&[], "");
+        assert_same(&utf16_bytes("Prüfung ✓\0"), "Prüfung ✓");
     }
 }
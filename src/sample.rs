@@ -0,0 +1,91 @@
+//! Random sampling for `--sample`.
+//!
+//! Every object gets a pseudo-random key, a hash of its upper-case CLSID and the seed, and the
+//! sample is the `size` objects with the smallest keys. This is reservoir sampling with
+//! priorities: it runs in one streaming pass with at most `size` objects held, every subset of
+//! that size is equally likely, and the outcome depends only on the seed, not on the order in
+//! which views or worker threads deliver the objects. A CLSID seen in both registry views gets
+//! the same key twice, so its two halves are merged instead of taking two places.
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::types::ComObject;
+
+/// The objects with the smallest sampling keys seen so far
+pub struct Sample {
+    size: usize,
+    seed: u64,
+    entries: BTreeMap<(u64, String), ComObject>,
+}
+
+impl Sample {
+    pub fn new(size: usize, seed: u64) -> Self {
+        Self {
+            size,
+            seed,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Keep the object if its key is among the `size` smallest so far
+    pub fn offer(&mut self, obj: ComObject) {
+        let key = (sample_key(&obj.clsid, self.seed), obj.clsid.to_uppercase());
+        if let Some(kept) = self.entries.get_mut(&key) {
+            kept.merge(obj);
+            return;
+        }
+        if self.entries.len() >= self.size {
+            match self.entries.last_key_value() {
+                Some((largest, _)) if key < *largest => {
+                    self.entries.pop_last();
+                }
+                _ => return,
+            }
+        }
+        self.entries.insert(key, obj);
+    }
+
+    pub fn into_objects(self) -> HashMap<String, ComObject> {
+        self.entries
+            .into_values()
+            .map(|obj| (obj.clsid.clone(), obj))
+            .collect()
+    }
+}
+
+/// Cut an already collected set down to the same sample a streaming pass would have kept
+pub fn retain(objects: &mut HashMap<String, ComObject>, size: usize, seed: u64) {
+    let mut sample = Sample::new(size, seed);
+    for (_, obj) in objects.drain() {
+        sample.offer(obj);
+    }
+    *objects = sample.into_objects();
+}
+
+/// A seed for runs without `--seed`, taken from the clock
+pub fn random_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+    mix(nanos)
+}
+
+/// FNV-1a over the upper-case CLSID, started from the mixed seed and mixed again at the end
+fn sample_key(clsid: &str, seed: u64) -> u64 {
+    let hash = clsid
+        .bytes()
+        .map(|byte| byte.to_ascii_uppercase())
+        .fold(mix(seed), |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01B3)
+        });
+    mix(hash)
+}
+
+/// The SplitMix64 finalizer, which spreads every input bit over the whole output
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
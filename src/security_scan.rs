@@ -0,0 +1,71 @@
+//! Composite suspicion score for `--suspicious`.
+//!
+//! Each factor is a security signal collected for an object; the score is the sum of the
+//! weights of the factors that apply. All weights live in [`SUSPICION_FACTORS`].
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+use crate::types::ComObject;
+
+/// Objects whose CLSID key was written within this many days count as recently modified
+const RECENT_DAYS: i64 = 30;
+
+/// A named signal with its weight and the check that decides whether it applies
+pub struct SuspicionFactor {
+    pub name: &'static str,
+    pub weight: u32,
+    check: fn(&ComObject) -> bool,
+}
+
+/// Every factor of the score with its weight
+pub const SUSPICION_FACTORS: &[SuspicionFactor] = &[
+    SuspicionFactor {
+        name: "CLSID key writable by standard users",
+        weight: 40,
+        check: |obj| obj.writable_by_users,
+    },
+    SuspicionFactor {
+        name: "per-user registration shadows the machine-wide server",
+        weight: 35,
+        check: |obj| obj.shadowed,
+    },
+    SuspicionFactor {
+        name: "server binary in a user-writable directory",
+        weight: 30,
+        check: |obj| obj.binary_hijackable,
+    },
+    SuspicionFactor {
+        name: "server binary does not exist",
+        weight: 25,
+        check: |obj| obj.server_missing,
+    },
+    SuspicionFactor {
+        name: "unquoted LocalServer32 path with spaces",
+        weight: 20,
+        check: |obj| obj.unquoted_path,
+    },
+    SuspicionFactor {
+        name: "CLSID key modified in the last 30 days",
+        weight: 10,
+        check: is_recently_modified,
+    },
+];
+
+/// The factors that apply to an object, in table order
+pub fn suspicion_factors(obj: &ComObject) -> Vec<&'static SuspicionFactor> {
+    SUSPICION_FACTORS.iter().filter(|factor| (factor.check)(obj)).collect()
+}
+
+/// Sets `suspicion_score` on every object to the summed weight of its factors
+pub fn score_objects(objects: &mut HashMap<String, ComObject>) {
+    for obj in objects.values_mut() {
+        obj.suspicion_score = suspicion_factors(obj).iter().map(|factor| factor.weight).sum();
+    }
+}
+
+/// True if the CLSID key was written within the last [`RECENT_DAYS`] days
+fn is_recently_modified(obj: &ComObject) -> bool {
+    let since = DateTime::<Utc>::from(std::time::SystemTime::now()) - Duration::days(RECENT_DAYS);
+    obj.last_write_time.is_some_and(|written| written > since)
+}
@@ -0,0 +1,75 @@
+//! Parsing of `InprocServer32` and `LocalServer32` values.
+//!
+//! An `InprocServer32` default value is a DLL path; a `LocalServer32` value is a command line
+//! that may be quoted (`"C:\Program Files\App\srv.exe" -Embedding`) or not
+//! (`C:\Windows\srv.exe /automation`). Both may contain `%VAR%` references.
+
+use std::path::PathBuf;
+
+/// Extensions of server binaries, used to find where an unquoted path ends
+const BINARY_EXTENSIONS: &[&str] = &[".exe", ".dll", ".ocx", ".cpl", ".ax", ".com"];
+
+/// Extracts the binary path from a server value, expanding environment variables.
+/// A quoted value ends at the closing quote. An unquoted value ends after the first known
+/// binary extension that is followed by whitespace or the end of the value, or at the first
+/// whitespace if there is none.
+pub fn server_binary(value: &str) -> Option<PathBuf> {
+    let value = expand_env_vars(value.trim());
+    let path = match value.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next().unwrap_or_default(),
+        None => unquoted_path(&value),
+    };
+
+    if path.is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(path))
+    }
+}
+
+/// Returns true for an unquoted command line whose executable path contains whitespace, such
+/// as `C:\Program Files\App\srv.exe -Embedding`: Windows tries `C:\Program.exe` first, so
+/// anyone who can write to `C:\` can run code in place of the server.
+/// `"C:\Program Files\App\srv.exe" -Embedding` and `C:\Windows\srv.exe /automation` are safe.
+pub fn is_unquoted_with_spaces(value: &str) -> bool {
+    let value = value.trim();
+    !value.starts_with('"')
+        && server_binary(value)
+            .is_some_and(|path| path.to_string_lossy().contains(char::is_whitespace))
+}
+
+/// The path part of an unquoted server value
+fn unquoted_path(value: &str) -> &str {
+    let lower = value.to_ascii_lowercase();
+    let end = BINARY_EXTENSIONS
+        .iter()
+        .flat_map(|ext| lower.match_indices(ext).map(move |(index, _)| index + ext.len()))
+        .filter(|&end| lower[end..].chars().next().is_none_or(char::is_whitespace))
+        .min();
+
+    match end {
+        Some(end) => &value[..end],
+        None => value.split_whitespace().next().unwrap_or_default(),
+    }
+}
+
+/// Replaces `%NAME%` references with the value of the environment variable, leaving unknown
+/// variables as they are (like `ExpandEnvironmentStrings`)
+fn expand_env_vars(value: &str) -> String {
+    let mut expanded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('%') {
+        let Some(len) = rest[start + 1..].find('%') else {
+            break;
+        };
+        let name = &rest[start + 1..start + 1 + len];
+        expanded.push_str(&rest[..start]);
+        match std::env::var(name) {
+            Ok(var) if !name.is_empty() => expanded.push_str(&var),
+            _ => expanded.push_str(&rest[start..start + len + 2]),
+        }
+        rest = &rest[start + len + 2..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
@@ -0,0 +1,32 @@
+//! Windows shell integration.
+//!
+//! This module opens exported files with the application registered for their type.
+
+use anyhow::Result;
+use windows::core::{HSTRING, PCWSTR};
+use windows::Win32::Foundation::HWND;
+use windows::Win32::UI::Shell::ShellExecuteW;
+use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+/// Open a file with the "open" verb, so HTML opens in the browser, CSV in Excel, etc.
+pub fn open_file(path: &str) -> Result<()> {
+    unsafe {
+        let result = ShellExecuteW(
+            HWND::default(),
+            &HSTRING::from("open"),
+            &HSTRING::from(path),
+            PCWSTR::null(),
+            PCWSTR::null(),
+            SW_SHOWNORMAL,
+        );
+
+        // Values above 32 indicate success; anything else is an error code
+        if result.0 <= 32 {
+            return Err(anyhow::anyhow!(
+                "Failed to open {path}: error code {}",
+                result.0
+            ));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! Shell extension registrations for `--shell-ext`.
+//!
+//! Explorer finds its extensions in three places: the `Shell Extensions\Approved` list, the
+//! `ShellIconOverlayIdentifiers` key, and the `shellex` subkey of file types and classes such
+//! as `*`, `Directory` or `.txt`. `registry::read_shell_extensions` collects them; this module
+//! names the handler types and attaches them to the scanned objects.
+
+use std::collections::HashMap;
+
+use crate::filter::normalize_clsid;
+use crate::interfaces::IidResolver;
+use crate::types::{ComObject, ShellExtension};
+
+/// Handler interfaces that are registered as an IID subkey of `shellex` (whose default value
+/// is the handler CLSID) instead of a named handler list
+pub const WELL_KNOWN_HANDLER_INTERFACES: &[(&str, &str)] = &[
+    ("{00021500-0000-0000-C000-000000000046}", "InfoTipHandler"),
+    ("{BB2E617C-0920-11D1-9A0B-00C04FC2D6C1}", "ThumbnailHandler (IExtractImage)"),
+    ("{E357FCCD-A995-4576-B01F-234630154E96}", "ThumbnailHandler"),
+    ("{8895B1C6-B41F-4C1C-A562-0D564250836F}", "PreviewHandler"),
+];
+
+/// The handler type shown for a `shellex` subkey: the subkey name (e.g.
+/// `ContextMenuHandlers`), the handler name for a well-known IID subkey, or the interface
+/// name of any other IID subkey that can be resolved
+pub fn handler_type_name(subkey: &str, interface_names: &mut IidResolver) -> String {
+    let known = WELL_KNOWN_HANDLER_INTERFACES
+        .iter()
+        .find(|(iid, _)| iid.eq_ignore_ascii_case(subkey))
+        .map(|(_, name)| name.to_string());
+    known
+        .or_else(|| subkey.starts_with('{').then(|| interface_names.iid_to_name(subkey)).flatten())
+        .unwrap_or_else(|| subkey.to_string())
+}
+
+/// Collect the distinct handler types of every registered CLSID (keyed by its
+/// [`normalize_clsid`] form), in the order they were first registered
+pub fn handlers_by_clsid(extensions: &[ShellExtension]) -> HashMap<String, Vec<String>> {
+    let mut handlers: HashMap<String, Vec<String>> = HashMap::new();
+    for extension in extensions {
+        let types = handlers.entry(normalize_clsid(&extension.clsid)).or_default();
+        if !types.contains(&extension.handler_type) {
+            types.push(extension.handler_type.clone());
+        }
+    }
+    handlers
+}
+
+/// Sets `shell_handlers` on every object registered as a shell extension
+pub fn annotate(
+    objects: &mut HashMap<String, ComObject>,
+    handlers: &HashMap<String, Vec<String>>,
+) {
+    for obj in objects.values_mut() {
+        if let Some(types) = handlers.get(&normalize_clsid(&obj.clsid)) {
+            obj.shell_handlers = types.clone();
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Authenticode signer lookup for `--check-signers`.
+//!
+//! A binary is either signed itself (an embedded PKCS #7 signature) or listed in a signed
+//! security catalog, which is how most files shipped with Windows are signed. The signer is
+//! the simple display name (usually the subject common name) of the signing certificate.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use windows::core::{HSTRING, PCWSTR};
+use windows::Win32::Foundation::{CloseHandle, GENERIC_READ, HANDLE};
+use windows::Win32::Security::Cryptography::Catalog::{
+    CryptCATAdminAcquireContext2, CryptCATAdminCalcHashFromFileHandle2,
+    CryptCATAdminEnumCatalogFromHash, CryptCATAdminReleaseCatalogContext,
+    CryptCATAdminReleaseContext, CryptCATCatalogInfoFromContext, CATALOG_INFO,
+};
+use windows::Win32::Security::Cryptography::{
+    CertCloseStore, CertFindCertificateInStore, CertFreeCertificateContext, CertGetNameStringW,
+    CryptMsgClose, CryptMsgGetParam, CryptQueryObject, CERT_FIND_SUBJECT_CERT, CERT_INFO,
+    CERT_NAME_SIMPLE_DISPLAY_TYPE, CERT_QUERY_CONTENT_FLAG_PKCS7_SIGNED,
+    CERT_QUERY_CONTENT_FLAG_PKCS7_SIGNED_EMBED, CERT_QUERY_CONTENT_TYPE_FLAGS,
+    CERT_QUERY_ENCODING_TYPE,
+    CERT_QUERY_FORMAT_FLAG_BINARY, CERT_QUERY_OBJECT_FILE, CMSG_SIGNER_INFO,
+    CMSG_SIGNER_INFO_PARAM, HCERTSTORE, PKCS_7_ASN_ENCODING, X509_ASN_ENCODING,
+};
+use windows::Win32::Storage::FileSystem::{
+    CreateFileW, FILE_ATTRIBUTE_NORMAL, FILE_SHARE_READ, OPEN_EXISTING,
+};
+
+use crate::types::ComObject;
+
+/// Sets `signer` on every object from its server binary (InprocServer32, else LocalServer32).
+/// Each binary is checked once; bare file names and missing files get no signer.
+pub fn check_signers(objects: &mut HashMap<String, ComObject>) {
+    let mut signers: HashMap<PathBuf, Option<String>> = HashMap::new();
+    for obj in objects.values_mut() {
+        obj.signer = obj.server_binary().and_then(|path| {
+            signers.entry(path).or_insert_with_key(|path| signer(path)).clone()
+        });
+    }
+}
+
+/// The signer of a file: its embedded signature, or else the catalog that lists it
+pub fn signer(path: &Path) -> Option<String> {
+    signed_file_signer(path).or_else(|| catalog_file(path).and_then(|cat| signed_file_signer(&cat)))
+}
+
+/// Reads the first signer of a file carrying a PKCS #7 signature (an embedded Authenticode
+/// signature or a catalog file) and returns the display name of its certificate
+fn signed_file_signer(path: &Path) -> Option<String> {
+    let name = HSTRING::from(path.to_string_lossy().as_ref());
+    let mut store = HCERTSTORE::default();
+    let mut message: *mut core::ffi::c_void = std::ptr::null_mut();
+    let content = CERT_QUERY_CONTENT_TYPE_FLAGS(
+        CERT_QUERY_CONTENT_FLAG_PKCS7_SIGNED_EMBED.0 | CERT_QUERY_CONTENT_FLAG_PKCS7_SIGNED.0,
+    );
+
+    unsafe {
+        CryptQueryObject(
+            CERT_QUERY_OBJECT_FILE,
+            name.as_ptr() as *const _,
+            content,
+            CERT_QUERY_FORMAT_FLAG_BINARY,
+            0,
+            None,
+            None,
+            None,
+            Some(&mut store),
+            Some(&mut message),
+            None,
+        )
+        .ok()?;
+
+        let signer = message_signer(store, message);
+        let _ = CryptMsgClose(Some(message));
+        let _ = CertCloseStore(store, 0);
+        signer
+    }
+}
+
+/// Finds the certificate of the message's first signer in the message store
+unsafe fn message_signer(store: HCERTSTORE, message: *const core::ffi::c_void) -> Option<String> {
+    let mut size = 0u32;
+    CryptMsgGetParam(message, CMSG_SIGNER_INFO_PARAM, 0, None, &mut size).ok()?;
+    // u64 elements keep the buffer aligned for the pointers inside CMSG_SIGNER_INFO
+    let mut buffer = vec![0u64; (size as usize).div_ceil(8)];
+    let data = Some(buffer.as_mut_ptr() as *mut _);
+    CryptMsgGetParam(message, CMSG_SIGNER_INFO_PARAM, 0, data, &mut size).ok()?;
+    let signer_info = &*(buffer.as_ptr() as *const CMSG_SIGNER_INFO);
+
+    let cert_info = CERT_INFO {
+        Issuer: signer_info.Issuer,
+        SerialNumber: signer_info.SerialNumber,
+        ..Default::default()
+    };
+    let encoding: CERT_QUERY_ENCODING_TYPE = X509_ASN_ENCODING | PKCS_7_ASN_ENCODING;
+    let cert = CertFindCertificateInStore(
+        store,
+        encoding,
+        0,
+        CERT_FIND_SUBJECT_CERT,
+        Some(&cert_info as *const _ as *const _),
+        None,
+    );
+    if cert.is_null() {
+        return None;
+    }
+
+    let mut name = [0u16; 256];
+    let len = CertGetNameStringW(cert, CERT_NAME_SIMPLE_DISPLAY_TYPE, 0, None, Some(&mut name));
+    let _ = CertFreeCertificateContext(Some(cert));
+
+    // The length includes the terminating null; 1 means an empty name
+    (len > 1).then(|| String::from_utf16_lossy(&name[..len as usize - 1]))
+}
+
+/// Looks up the security catalog that lists the file's hash
+fn catalog_file(path: &Path) -> Option<PathBuf> {
+    let name = HSTRING::from(path.to_string_lossy().as_ref());
+    unsafe {
+        let file = CreateFileW(
+            &name,
+            GENERIC_READ.0,
+            FILE_SHARE_READ,
+            None,
+            OPEN_EXISTING,
+            FILE_ATTRIBUTE_NORMAL,
+            HANDLE::default(),
+        )
+        .ok()?;
+
+        // Current catalogs use SHA-256 hashes, older ones SHA-1
+        let catalog = ["SHA256", "SHA1"]
+            .into_iter()
+            .find_map(|algorithm| catalog_for_hash(file, algorithm));
+        let _ = CloseHandle(file);
+        catalog
+    }
+}
+
+/// Hashes the open file with the given algorithm and returns the first catalog listing it
+unsafe fn catalog_for_hash(file: HANDLE, algorithm: &str) -> Option<PathBuf> {
+    let algorithm = HSTRING::from(algorithm);
+    let mut admin = 0isize;
+    CryptCATAdminAcquireContext2(&mut admin, None, PCWSTR(algorithm.as_ptr()), None, 0).ok()?;
+
+    let mut hash = [0u8; 64];
+    let mut hash_len = hash.len() as u32;
+    let mut catalog = None;
+    let hash_buffer = Some(hash.as_mut_ptr());
+    let hashed = CryptCATAdminCalcHashFromFileHandle2(admin, file, &mut hash_len, hash_buffer, 0);
+    if hashed.is_ok() {
+        let hash = &hash[..hash_len as usize];
+        let cat_context = CryptCATAdminEnumCatalogFromHash(admin, hash, 0, None);
+        if cat_context != 0 {
+            let mut info = CATALOG_INFO {
+                cbStruct: std::mem::size_of::<CATALOG_INFO>() as u32,
+                ..Default::default()
+            };
+            if CryptCATCatalogInfoFromContext(cat_context, &mut info, 0).is_ok() {
+                let len = info.wszCatalogFile.iter().position(|&c| c == 0).unwrap_or(0);
+                let path = String::from_utf16_lossy(&info.wszCatalogFile[..len]);
+                catalog = Some(PathBuf::from(path));
+            }
+            let _ = CryptCATAdminReleaseCatalogContext(admin, cat_context, 0);
+        }
+    }
+    let _ = CryptCATAdminReleaseContext(admin, 0);
+    catalog
+}
@@ -1,27 +1,111 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::server_path::{is_unquoted_with_spaces, server_binary};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::IsTerminal;
+use std::path::PathBuf;
 
 /// A Rust CLI for Windows that discovers COM objects and checks their programmatic usability
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = None,
+    after_help = "Environment variables seed --filter, --format, --output, --columns, --sort, \
+        --root, --threads and --usability-weights (OLE_INSPECTOR_FILTER and so on); a flag on \
+        the command line wins. No other option reads the environment, and there is no \
+        configuration file."
+)]
 pub struct Args {
-    /// Show detailed information for each COM object
+    /// Show detailed information for each COM object; repeat for more detail
+    /// (-v listing, -vv server paths and categories, -vvv every captured field)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Print only the statistics of the results (ProgID share, threading models, vendors)
+    /// instead of listing the objects
+    #[arg(long)]
+    pub summary: bool,
+
+    /// Write the statistics of the results (counts, threading models, vendors, scan duration,
+    /// views scanned) as a JSON object to this file, for dashboards that trend them
+    #[arg(long, value_name = "PATH")]
+    pub stats_json: Option<String>,
+
+    /// In the detailed listing, show the registry type (REG_SZ, REG_EXPAND_SZ, ...) of the
+    /// ProgID, description and server values
+    #[arg(long)]
+    pub show_value_types: bool,
+
+    /// Print registry values and write them to TXT/CSV/TSV exports exactly as stored, instead
+    /// of escaping control characters such as newlines and escape sequences
+    #[arg(long)]
+    pub raw_values: bool,
+
+    /// Suppress scan progress and timing output
     #[arg(short, long)]
-    pub verbose: bool,
+    pub quiet: bool,
 
-    /// Scan 32-bit registry view
+    /// Show an indeterminate spinner with the progress counter (ignored when stderr is not
+    /// a terminal)
+    #[arg(long)]
+    pub spinner: bool,
+
+    /// When to color console reports such as the `--baseline` comparison: `auto` colors an
+    /// interactive console unless `NO_COLOR` is set
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// Scan only the 32-bit registry view (both views are scanned unless exactly one of
+    /// `--scan-32bit` and `--scan-64bit` is given)
     #[arg(long)]
     pub scan_32bit: bool,
 
-    /// Scan 64-bit registry view (default on 64-bit systems)
+    /// Scan only the 64-bit registry view (both views are scanned unless exactly one of
+    /// `--scan-32bit` and `--scan-64bit` is given)
     #[arg(long)]
     pub scan_64bit: bool,
 
+    /// Registry root to scan for CLSIDs
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = RegistryRoot::ClassesRoot,
+        env = "OLE_INSPECTOR_ROOT"
+    )]
+    pub root: RegistryRoot,
+
+    /// Read the HKLM and HKCU classes trees directly and resolve them with HKCR's
+    /// precedence instead of scanning `--root`
+    #[arg(long, conflicts_with_all = ["root", "cache"])]
+    pub resolve_classes: bool,
+
     /// Limit the number of results (0 = no limit)
     #[arg(short, long, default_value = "0")]
     pub limit: usize,
 
+    /// Worker threads reading CLSID keys (0 = one per CPU, 1 = scan on a single thread)
+    #[arg(long, value_name = "N", default_value_t = 0, env = "OLE_INSPECTOR_THREADS")]
+    pub threads: usize,
+
+    /// Keep a uniformly random sample of this many matching objects
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub sample: Option<u32>,
+
+    /// Seed for `--sample`, so the same registry state yields the same sample
+    #[arg(long, value_name = "N", requires = "sample")]
+    pub seed: Option<u64>,
+
+    /// Stop scanning after this many seconds and keep the partial results (exit code 124)
+    #[arg(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+
     /// Filter by ProgID, description, or CLSID substring (case-insensitive)
-    #[arg(short, long)]
+    #[arg(short, long, env = "OLE_INSPECTOR_FILTER")]
     pub filter: Option<String>,
 
     /// Filter by description substring only (case-insensitive)
@@ -32,15 +116,1052 @@ pub struct Args {
     #[arg(long)]
     pub filter_clsid: Option<String>,
 
+    /// Filter by ProgID substring only (case-insensitive)
+    #[arg(long)]
+    pub filter_progid: Option<String>,
+
+    /// Only include objects with this AppID (exact match, braces and case ignored)
+    #[arg(long, value_name = "GUID")]
+    pub filter_appid: Option<String>,
+
     /// Filter by application keywords (comma-separated, case-insensitive)
     #[arg(long, value_delimiter = ',')]
     pub filter_app: Option<Vec<String>>,
+
+    /// Add a built-in keyword set to the application filter (comma-separated or repeated; presets union)
+    #[arg(long, value_delimiter = ',', value_parser = crate::filter::parse_preset)]
+    pub preset: Option<Vec<String>>,
+
+    /// How `--filter`, `--filter-description` and `--filter-clsid` combine with each other
+    #[arg(long, value_enum, default_value_t = FilterLogic::And)]
+    pub filter_logic: FilterLogic,
+
+    /// Match filters case-sensitively instead of ignoring case
+    #[arg(long)]
+    pub case_sensitive: bool,
+
+    /// Match ProgID/description filters by similarity score instead of exact substrings
+    #[arg(long)]
+    pub fuzzy: bool,
+
+    /// Minimum similarity score (0-100) for a fuzzy match
+    #[arg(long, default_value = "80", value_parser = clap::value_parser!(u8).range(0..=100))]
+    pub fuzzy_threshold: u8,
+
+    /// Only include objects whose CLSID key was last written after this date
+    /// (ISO 8601, e.g. 2024-05-01 or 2024-05-01T12:00:00Z; dates without a zone are UTC)
+    #[arg(long, value_name = "DATE", value_parser = crate::filter::parse_since)]
+    pub modified_since: Option<DateTime<Utc>>,
+
+    /// Only include objects whose CLSID key was last written within this many days, the
+    /// relative form of `--modified-since`
+    #[arg(long, value_name = "DAYS", conflicts_with = "modified_since")]
+    pub recent: Option<u32>,
+
+    /// Only include objects at or above this programmatic usability level
+    #[arg(long, value_enum)]
+    pub min_usability: Option<UsabilityLevel>,
+
+    /// JSON file mapping usability factors to integer weights, e.g. `{"prog_id": 1}`
+    #[arg(long, value_name = "PATH", env = "OLE_INSPECTOR_USABILITY_WEIGHTS")]
+    pub usability_weights: Option<String>,
+
+    /// Rate usability by ProgID and description only, ignoring whether the server exists
+    #[arg(long, conflicts_with = "usability_weights")]
+    pub legacy_usability: bool,
+
+    /// Only include objects marked safe for scripting or safe for initializing
+    #[arg(long)]
+    pub only_scriptable: bool,
+
+    /// Only include ActiveX controls (objects with a `Control` subkey)
+    #[arg(long)]
+    pub only_controls: bool,
+
+    /// Only include objects with a per-user (HKCU) registration, including ones that shadow a machine-wide entry
+    #[arg(long)]
+    pub only_user_scope: bool,
+
+    /// Only include insertable OLE document objects (objects with an `Insertable` subkey)
+    #[arg(long)]
+    pub only_insertable: bool,
+
+    /// Only include objects registered as a shell extension handler (context menu, icon
+    /// overlay, property sheet, ...)
+    #[arg(long)]
+    pub only_shell_ext: bool,
+
+    /// Only include objects whose LocalServer32 command line is unquoted and has spaces in
+    /// its path (a potential unquoted-path vulnerability)
+    #[arg(long)]
+    pub only_unquoted: bool,
+
+    /// Only include objects that register a TypeLib but no ProgID (usable through early
+    /// binding, but not by name)
+    #[arg(long)]
+    pub only_typelib_no_progid: bool,
+
+    /// Read the security descriptor of every CLSID key and flag keys that standard users
+    /// (Users, Everyone, Authenticated Users, Interactive) can write to
+    #[arg(long)]
+    pub check_acls: bool,
+
+    /// Only include objects whose CLSID key is writable by standard users (implies --check-acls)
+    #[arg(long)]
+    pub only_writable: bool,
+
+    /// Flag objects whose InprocServer32/LocalServer32 binary is in a directory that standard
+    /// users can write to (a COM hijack precondition)
+    #[arg(long)]
+    pub check_binary_perms: bool,
+
+    /// Look up the code signer of each server binary (embedded signature or security catalog)
+    #[arg(long)]
+    pub check_signers: bool,
+
+    /// Only include objects whose signer contains this text; prefix with '!' to only include
+    /// objects not signed by it, including unsigned ones (implies --check-signers)
+    #[arg(long, value_name = "TEXT")]
+    pub filter_signer: Option<String>,
+
+    /// Read the company and product name from the version resource of each server binary
+    #[arg(long)]
+    pub check_version_info: bool,
+
+    /// After the scan, group the objects by vendor: the binary's signer (default) or the
+    /// CompanyName of its version resource (implies the matching check)
+    #[arg(
+        long,
+        value_enum,
+        value_name = "SOURCE",
+        num_args = 0..=1,
+        default_missing_value = "signer"
+    )]
+    pub group_by_vendor: Option<VendorSource>,
+
+    /// Write the vendor groups of `--group-by-vendor` to this JSON file
+    #[arg(long, value_name = "PATH", requires = "group_by_vendor")]
+    pub vendor_export: Option<String>,
+
+    /// Mark objects whose InprocServer32 DLL is currently loaded in a running process
+    #[arg(long)]
+    pub check_loaded: bool,
+
+    /// Try to create every listed object with CoCreateInstance (loads its DLL or starts its
+    /// server) and use the outcome in the usability rating
+    #[arg(long)]
+    pub probe: bool,
+
+    /// Only include objects that `--probe` could instantiate
+    #[arg(long, requires = "probe", conflicts_with = "only_not_instantiable")]
+    pub only_instantiable: bool,
+
+    /// Only include objects that `--probe` failed to instantiate
+    #[arg(long, requires = "probe")]
+    pub only_not_instantiable: bool,
+
+    /// Score objects by their security signals and show only those at or above
+    /// --suspicion-threshold, most suspicious first (implies --check-acls and
+    /// --check-binary-perms)
+    #[arg(long)]
+    pub suspicious: bool,
+
+    /// Minimum suspicion score shown by --suspicious
+    #[arg(long, value_name = "SCORE", default_value_t = 25)]
+    pub suspicion_threshold: u32,
+
+    /// Sort key for the listing and all exports
+    #[arg(long, value_enum, default_value_t = SortKey::ProgId, env = "OLE_INSPECTOR_SORT")]
+    pub sort: SortKey,
+
+    /// Reverse the sort order (e.g. `--sort usability --sort-desc` lists the least usable first)
+    #[arg(long)]
+    pub sort_desc: bool,
+
+    /// Prepend a UTF-8 byte order mark to TXT, CSV and TSV exports (for legacy tools such as older Excel)
+    #[arg(long)]
+    pub bom: bool,
+
+    /// Open the exported file with its default application after a successful export
+    #[arg(long)]
+    pub open: bool,
+
+    /// Write the export to this file instead of prompting; the format follows the file
+    /// extension unless `--format` is given
+    #[arg(short = 'o', long, value_name = "PATH", env = "OLE_INSPECTOR_OUTPUT")]
+    pub output: Option<String>,
+
+    /// Overwrite the `--output` file if it already exists
+    #[arg(long)]
+    pub force: bool,
+
+    /// Copy the export to the clipboard instead of writing a file
+    #[arg(long)]
+    pub clipboard: bool,
+
+    /// Export format used by `--clipboard` and `--output` (default: the `--output` file
+    /// extension, else txt)
+    #[arg(
+        long,
+        env = "OLE_INSPECTOR_FORMAT",
+        value_parser = clap::builder::PossibleValuesParser::new(crate::display::EXPORT_FORMATS)
+    )]
+    pub format: Option<String>,
+
+    /// Columns of CSV/TSV/HTML exports and keys of JSON exports (comma-separated; default: all)
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_parser = crate::display::parse_column,
+        env = "OLE_INSPECTOR_COLUMNS"
+    )]
+    pub columns: Option<Vec<String>>,
+
+    /// Write JSON exports on a single line instead of pretty-printed (smaller files for large scans)
+    #[arg(long)]
+    pub json_compact: bool,
+
+    /// After the scan, list ProgIDs that are claimed by more than one CLSID
+    #[arg(long)]
+    pub report_duplicates: bool,
+
+    /// After the scan, list CLSIDs that more than one ProgID points to
+    #[arg(long)]
+    pub report_multi_progid: bool,
+
+    /// After scanning both views, list fields that differ between the 32-bit and 64-bit
+    /// registrations of the same CLSID
+    #[arg(long)]
+    pub report_view_conflicts: bool,
+
+    /// Print a SHA-256 fingerprint of the scan results instead of displaying them
+    /// (skips all prompts)
+    #[arg(long)]
+    pub fingerprint: bool,
+
+    /// Compare the scan against a baseline JSON export and report objects that are new,
+    /// missing or changed; exits with status 3 if any deviate (skips all prompts)
+    #[arg(long, value_name = "PATH")]
+    pub baseline: Option<String>,
+
+    /// Write the complete, unfiltered scan to this JSON file when it finishes
+    #[arg(long, value_name = "PATH")]
+    pub cache: Option<String>,
+
+    /// Load the objects from the `--cache` file instead of scanning, if it is recent enough
+    /// and covers the same root and views
+    #[arg(long, requires = "cache")]
+    pub use_cache: bool,
+
+    /// Maximum age in seconds of a cache file loaded with `--use-cache`
+    #[arg(long, value_name = "SECONDS", default_value_t = 3600)]
+    pub cache_max_age: u64,
+
+    /// Run without prompts (no interactive filter, refinement loop, export prompt, or quit wait)
+    #[arg(long)]
+    pub non_interactive: bool,
+
+    /// Run the scan repeatedly and print timing statistics instead of results
+    #[arg(long, hide = true)]
+    pub bench: bool,
+
+    /// Number of scan iterations for `--bench`
+    #[arg(long, hide = true, default_value = "5", value_parser = clap::value_parser!(u32).range(1..))]
+    pub bench_iters: u32,
+
+    /// For objects with a `TreatAs` redirection, also read the ProgID, description and servers
+    /// of the class it redirects to
+    #[arg(long)]
+    pub follow_treatas: bool,
+
+    /// Read this named value from every CLSID key (repeatable), e.g. `FriendlyName`;
+    /// the values are listed with the object and added as `Extra:<name>` export columns
+    #[arg(long, value_name = "NAME")]
+    pub extra_value: Vec<String>,
+
+    /// Read only these CLSIDs (comma-separated or repeated, braces optional) instead of enumerating the whole hive
+    #[arg(long, value_name = "CLSID", value_delimiter = ',')]
+    pub lookup: Option<Vec<String>>,
+
+    /// Print every subkey and value below this CLSID's key, with value types and raw data,
+    /// instead of scanning (braces optional)
+    #[arg(long, value_name = "CLSID")]
+    pub dump: Option<String>,
+
+    /// List the Browser Helper Objects registered for Internet Explorer with their servers
+    /// instead of scanning all CLSIDs
+    #[arg(long)]
+    pub bho: bool,
+
+    /// List the registered shell extension handlers (context menu, icon overlay, property
+    /// sheet, ...) with their handler types instead of scanning all CLSIDs
+    #[arg(long)]
+    pub shell_ext: bool,
+
+    /// List the registered COM interfaces with their proxy/stub CLSIDs instead of scanning
+    /// CLSIDs
+    #[arg(long)]
+    pub interfaces: bool,
+
+    /// List every registered version of the given TypeLib GUID instead of scanning CLSIDs
+    #[arg(long, value_name = "GUID")]
+    pub typelib_versions: Option<String>,
+}
+
+/// The registry root that holds the scanned `CLSID` key
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RegistryRoot {
+    /// HKEY_CLASSES_ROOT (merged per-user and machine-wide view)
+    #[default]
+    #[value(name = "hkcr")]
+    #[serde(rename = "hkcr")]
+    ClassesRoot,
+    /// HKEY_LOCAL_MACHINE\SOFTWARE\Classes (machine-wide registrations)
+    #[value(name = "hklm")]
+    #[serde(rename = "hklm")]
+    LocalMachine,
+    /// HKEY_CURRENT_USER\Software\Classes (per-user registrations)
+    #[value(name = "hkcu")]
+    #[serde(rename = "hkcu")]
+    CurrentUser,
+}
+
+impl RegistryRoot {
+    /// Full registry path of the classes root, as shown in regedit
+    pub fn path(&self) -> &'static str {
+        match self {
+            RegistryRoot::ClassesRoot => "HKEY_CLASSES_ROOT",
+            RegistryRoot::LocalMachine => "HKEY_LOCAL_MACHINE\\SOFTWARE\\Classes",
+            RegistryRoot::CurrentUser => "HKEY_CURRENT_USER\\Software\\Classes",
+        }
+    }
+}
+
+/// How the text filters combine: all must match, or any one is enough
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FilterLogic {
+    #[default]
+    And,
+    Or,
+}
+
+/// When console reports use ANSI colors (`--color`)
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color when stdout is a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether output written to stdout should be colored
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => {
+                std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// The registry views a scan reads. Exactly one of `--scan-32bit` and `--scan-64bit` selects
+/// that view alone; neither or both select both views.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewSelection {
+    Both,
+    Only32,
+    Only64,
+}
+
+impl ViewSelection {
+    pub fn from_flags(scan_32bit: bool, scan_64bit: bool) -> Self {
+        match (scan_32bit, scan_64bit) {
+            (true, false) => ViewSelection::Only32,
+            (false, true) => ViewSelection::Only64,
+            (false, false) | (true, true) => ViewSelection::Both,
+        }
+    }
+
+    pub fn includes_32bit(self) -> bool {
+        self != ViewSelection::Only64
+    }
+
+    pub fn includes_64bit(self) -> bool {
+        self != ViewSelection::Only32
+    }
+}
+
+/// Field used to order results
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey {
+    /// By ProgID; objects without one follow, ordered by CLSID
+    #[default]
+    #[value(name = "progid")]
+    ProgId,
+    /// By CLSID
+    Clsid,
+    /// By description (case-insensitive); objects without one follow
+    Description,
+    /// From most to least usable
+    Usability,
+    /// From most to least suspicious (`--suspicious` score)
+    Suspicion,
+}
+
+/// Where `--group-by-vendor` takes an object's vendor from
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VendorSource {
+    /// Display name of the certificate that signed the server binary
+    Signer,
+    /// CompanyName from the server binary's version resource
+    Company,
+}
+
+impl VendorSource {
+    /// The object's vendor according to this source
+    pub fn vendor(self, obj: &ComObject) -> Option<&str> {
+        match self {
+            VendorSource::Signer => obj.signer.as_deref(),
+            VendorSource::Company => obj.company_name.as_deref(),
+        }
+    }
+
+    /// Name of the source as shown in report headings
+    pub fn label(self) -> &'static str {
+        match self {
+            VendorSource::Signer => "signer",
+            VendorSource::Company => "company name",
+        }
+    }
+}
+
+/// Programmatic usability rating, ordered from least to most usable
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum UsabilityLevel {
+    VeryLow,
+    Low,
+    Medium,
+    High,
+}
+
+impl UsabilityLevel {
+    /// Human-readable verdict shown in listings and exports
+    pub fn label(&self) -> &'static str {
+        match self {
+            UsabilityLevel::High => "✓ High",
+            UsabilityLevel::Medium => "~ Medium",
+            UsabilityLevel::Low => "~ Low",
+            UsabilityLevel::VeryLow => "✗ Very Low",
+        }
+    }
+}
+
+/// Where a CLSID is registered: per-user (HKCU classes), machine-wide (HKLM classes), or both
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistrationScope {
+    User,
+    Machine,
+    Both,
+}
+
+impl RegistrationScope {
+    /// Combine the scopes seen in two registry views
+    pub fn combine(self, other: RegistrationScope) -> RegistrationScope {
+        if self == other {
+            self
+        } else {
+            RegistrationScope::Both
+        }
+    }
+}
+
+impl fmt::Display for RegistrationScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistrationScope::User => write!(f, "User (per-user only)"),
+            RegistrationScope::Machine => write!(f, "Machine (machine-wide only)"),
+            RegistrationScope::Both => write!(f, "Both (per-user and machine-wide)"),
+        }
+    }
 }
 
 /// Represents a COM object found in the Windows registry
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComObject {
     pub clsid: String,
+    /// Registry root the object was found under
+    #[serde(default)]
+    pub root: RegistryRoot,
+    /// Whether the CLSID exists under HKCU classes, HKLM classes, or both
+    #[serde(default)]
+    pub scope: Option<RegistrationScope>,
     pub prog_id: Option<String>,
+    /// Current versioned ProgID reached by following `CurVer`, if it differs from `prog_id`
+    #[serde(default)]
+    pub resolved_prog_id: Option<String>,
     pub description: Option<String>,
+    /// Default value of the `InprocServer32` subkey (DLL implementing an in-process server)
+    #[serde(default)]
+    pub inproc_server: Option<String>,
+    /// `ThreadingModel` value of the `InprocServer32` subkey (`Apartment`, `Free`, `Both`,
+    /// `Neutral`); an in-process server without one runs in the main single-threaded apartment
+    #[serde(default)]
+    pub threading_model: Option<String>,
+    /// Default value of the `LocalServer32` subkey (EXE command line of an out-of-process server)
+    #[serde(default)]
+    pub local_server: Option<String>,
+    /// Registry types the ProgID, description and server values are stored as
+    #[serde(default)]
+    pub value_types: ValueTypes,
+    /// `AppID` value linking the class to its DCOM application (`HKCR\AppID\{guid}`)
+    #[serde(default)]
+    pub app_id: Option<String>,
+    /// `RunAs` value of the AppID: the account the DCOM server is launched as
+    #[serde(default)]
+    pub run_as: Option<String>,
+    /// Default value of the `TypeLib` subkey: the LIBID of the type library describing the
+    /// class, which lets clients bind to its interfaces early
+    #[serde(default)]
+    pub type_lib: Option<String>,
+    /// Default value of the `TreatAs` subkey: the CLSID COM creates instead of this one
+    #[serde(default)]
+    pub treat_as: Option<String>,
+    /// The class the `TreatAs` chain ends at (set by `--follow-treatas`)
+    #[serde(default)]
+    pub treat_as_target: Option<TreatAsTarget>,
+    /// Categories listed under the object's `Implemented Categories` subkey
+    #[serde(default)]
+    pub implemented_categories: Vec<ComponentCategory>,
+    /// Derived from the implemented categories (safe for scripting or initializing)
+    #[serde(default)]
+    pub safe_for_scripting: bool,
+    /// True if the CLSID key has a `Control` subkey (ActiveX control)
+    #[serde(default)]
+    pub is_control: bool,
+    /// True if the CLSID key has an `Insertable` subkey (embeddable OLE document)
+    #[serde(default)]
+    pub is_insertable: bool,
+    /// When the CLSID key was last written, from `RegQueryInfoKeyW`
+    #[serde(default)]
+    pub last_write_time: Option<DateTime<Utc>>,
+    /// True if the CLSID key's DACL lets standard users write to it (set by `--check-acls`)
+    #[serde(default)]
+    pub writable_by_users: bool,
+    /// True if a server binary sits in a directory standard users can write to
+    /// (set by `--check-binary-perms`)
+    #[serde(default)]
+    pub binary_hijackable: bool,
+    /// True if the `LocalServer32` command line is unquoted and its path contains spaces
+    #[serde(default)]
+    pub unquoted_path: bool,
+    /// True if an `InprocServer32` or `LocalServer32` names an absolute path that does not exist
+    #[serde(default)]
+    pub server_missing: bool,
+    /// Weighted sum of the security signals that apply (set by `--suspicious`)
+    #[serde(default)]
+    pub suspicion_score: u32,
+    /// True if the InprocServer32 DLL is loaded in a running process (set by `--check-loaded`)
+    #[serde(default)]
+    pub currently_loaded: bool,
+    /// Whether CoCreateInstance created the class; `None` unless `--probe` ran
+    #[serde(default)]
+    pub instantiable: Option<bool>,
+    /// Display name of the certificate that signed the server binary (set by `--check-signers`)
+    #[serde(default)]
+    pub signer: Option<String>,
+    /// CompanyName from the server binary's version resource (set by `--check-version-info`)
+    #[serde(default)]
+    pub company_name: Option<String>,
+    /// ProductName from the server binary's version resource (set by `--check-version-info`)
+    #[serde(default)]
+    pub product_name: Option<String>,
+    /// Shell extension handler types the CLSID is registered as, such as
+    /// `ContextMenuHandlers` (set by `--shell-ext`)
+    #[serde(default)]
+    pub shell_handlers: Vec<String>,
+    /// Handler-type subkeys of the object's own `CLSID\{guid}\shellex` key
+    #[serde(default)]
+    pub shellex: Vec<String>,
+    /// True if a per-user registration overrides the machine-wide server of the class
+    /// (set by `--resolve-classes`)
+    #[serde(default)]
+    pub shadowed: bool,
+    /// Named values of the CLSID key requested with `--extra-value`, keyed by the name as
+    /// given; values that are not set are left out. Ordered by name so exports are stable.
+    #[serde(default)]
+    pub extra: BTreeMap<String, String>,
+}
+
+impl ComObject {
+    /// An object with only its CLSID and root, before any value has been read
+    pub fn new(clsid: String, root: RegistryRoot) -> Self {
+        Self {
+            clsid,
+            root,
+            scope: None,
+            prog_id: None,
+            resolved_prog_id: None,
+            description: None,
+            inproc_server: None,
+            threading_model: None,
+            value_types: ValueTypes::default(),
+            local_server: None,
+            app_id: None,
+            run_as: None,
+            type_lib: None,
+            treat_as: None,
+            treat_as_target: None,
+            implemented_categories: Vec::new(),
+            safe_for_scripting: false,
+            is_control: false,
+            is_insertable: false,
+            last_write_time: None,
+            writable_by_users: false,
+            binary_hijackable: false,
+            unquoted_path: false,
+            server_missing: false,
+            suspicion_score: 0,
+            currently_loaded: false,
+            instantiable: None,
+            signer: None,
+            company_name: None,
+            product_name: None,
+            shell_handlers: Vec::new(),
+            shellex: Vec::new(),
+            shadowed: false,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    /// Best-effort friendly name, as OLE/COM viewers show it: the description, else the
+    /// ProgID, else the ProductName of the server binary (`--check-version-info`), else the CLSID
+    pub fn display_name(&self) -> &str {
+        self.description
+            .as_deref()
+            .or(self.prog_id.as_deref())
+            .or(self.product_name.as_deref())
+            .unwrap_or(&self.clsid)
+    }
+
+    /// True if the DCOM server is launched as a fixed account instead of the interactive
+    /// user, which gives every caller that account's privileges
+    pub fn runs_as_fixed_account(&self) -> bool {
+        self.run_as
+            .as_deref()
+            .is_some_and(|identity| !identity.eq_ignore_ascii_case("Interactive User"))
+    }
+
+    /// True if the class registers a TypeLib but no ProgID: it cannot be created by name, but
+    /// its interfaces are usable through early binding (`--only-typelib-no-progid`)
+    pub fn has_typelib_without_prog_id(&self) -> bool {
+        self.type_lib.is_some() && self.prog_id.is_none()
+    }
+
+    /// Full registry path of the object's CLSID key, ready to paste into regedit
+    pub fn registry_path(&self) -> String {
+        format!("{}\\CLSID\\{}", self.root.path(), self.clsid)
+    }
+
+    /// Absolute path of the server binary: the InprocServer32 DLL, else the LocalServer32
+    /// executable. Bare file names are resolved by the loader at run time, so they have none.
+    pub fn server_binary(&self) -> Option<PathBuf> {
+        [&self.inproc_server, &self.local_server]
+            .into_iter()
+            .flatten()
+            .filter_map(|server| server_binary(server))
+            .find(|path| path.is_absolute())
+    }
+
+    /// Checks the file system for `server_missing`. Bare file names are resolved through the
+    /// search path and are not checked.
+    pub fn has_missing_server(&self) -> bool {
+        [&self.inproc_server, &self.local_server]
+            .into_iter()
+            .flatten()
+            .filter_map(|server| server_binary(server))
+            .any(|path| path.is_absolute() && !path.exists())
+    }
+
+    /// Merge data found for the same CLSID in another registry view,
+    /// filling in only the fields that are still missing
+    pub fn merge(&mut self, other: ComObject) {
+        if self.prog_id.is_none() {
+            self.prog_id = other.prog_id;
+            self.resolved_prog_id = other.resolved_prog_id;
+            self.value_types.prog_id = other.value_types.prog_id;
+        }
+        if self.description.is_none() {
+            self.description = other.description;
+            self.value_types.description = other.value_types.description;
+        }
+        if self.inproc_server.is_none() {
+            self.inproc_server = other.inproc_server;
+            self.threading_model = other.threading_model;
+            self.value_types.inproc_server = other.value_types.inproc_server;
+        }
+        if self.local_server.is_none() {
+            self.local_server = other.local_server;
+            self.value_types.local_server = other.value_types.local_server;
+        }
+        if self.app_id.is_none() {
+            self.app_id = other.app_id;
+            self.run_as = other.run_as;
+        }
+        if self.type_lib.is_none() {
+            self.type_lib = other.type_lib;
+        }
+        if self.treat_as.is_none() {
+            self.treat_as = other.treat_as;
+        }
+        if self.treat_as_target.is_none() {
+            self.treat_as_target = other.treat_as_target;
+        }
+        // Keep the most recent write of the two views
+        self.last_write_time = self.last_write_time.max(other.last_write_time);
+        self.writable_by_users |= other.writable_by_users;
+        self.binary_hijackable |= other.binary_hijackable;
+        self.currently_loaded |= other.currently_loaded;
+        self.instantiable = self.instantiable.or(other.instantiable);
+        self.shadowed |= other.shadowed;
+        if self.signer.is_none() {
+            self.signer = other.signer;
+        }
+        if self.company_name.is_none() {
+            self.company_name = other.company_name;
+            self.product_name = other.product_name;
+        }
+        self.unquoted_path = self.local_server.as_deref().is_some_and(is_unquoted_with_spaces);
+        self.server_missing = self.has_missing_server();
+        for handler in other.shell_handlers {
+            if !self.shell_handlers.contains(&handler) {
+                self.shell_handlers.push(handler);
+            }
+        }
+        for handler in other.shellex {
+            if !self.shellex.contains(&handler) {
+                self.shellex.push(handler);
+            }
+        }
+        for (name, value) in other.extra {
+            self.extra.entry(name).or_insert(value);
+        }
+        for category in other.implemented_categories {
+            if !self
+                .implemented_categories
+                .iter()
+                .any(|c| c.catid.eq_ignore_ascii_case(&category.catid))
+            {
+                self.implemented_categories.push(category);
+            }
+        }
+        self.safe_for_scripting |= other.safe_for_scripting;
+        self.is_control |= other.is_control;
+        self.is_insertable |= other.is_insertable;
+        self.scope = match (self.scope, other.scope) {
+            (Some(a), Some(b)) => Some(a.combine(b)),
+            (a, b) => a.or(b),
+        };
+    }
+}
+
+/// The class a `TreatAs` redirection resolves to, read from its own CLSID key
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TreatAsTarget {
+    pub clsid: String,
+    pub prog_id: Option<String>,
+    pub description: Option<String>,
+    pub inproc_server: Option<String>,
+    pub local_server: Option<String>,
+    /// Redirections followed to reach the class; more than 1 for a chain of `TreatAs` keys
+    pub hops: usize,
+    /// True if the chain led back to a CLSID already visited, so it was cut there
+    pub cycle: bool,
+}
+
+impl fmt::Display for TreatAsTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.prog_id {
+            Some(ref prog_id) => write!(f, "{} ({})", self.clsid, prog_id),
+            None => write!(f, "{}", self.clsid),
+        }
+    }
+}
+
+/// The `REG_*` type codes of an object's string values, `None` where the value was not read.
+/// `--show-value-types` lists them next to the values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueTypes {
+    pub prog_id: Option<u32>,
+    pub description: Option<u32>,
+    pub inproc_server: Option<u32>,
+    pub local_server: Option<u32>,
+}
+
+/// A component category implemented by a COM object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentCategory {
+    /// Raw category GUID (CATID) as found in the registry
+    pub catid: String,
+    /// Human-readable name, from the well-known table or `Component Categories`
+    pub name: Option<String>,
+}
+
+impl fmt::Display for ComponentCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name {
+            Some(ref name) => write!(f, "{} ({})", name, self.catid),
+            None => write!(f, "{}", self.catid),
+        }
+    }
+}
+
+/// A flat record of a focused scan that is not about CLSIDs, such as `--interfaces`.
+/// Implementing it is enough to list and export the records with [`ResultSet`].
+pub trait Record: Serialize {
+    /// Column names, used as labels in the listing and as the CSV/TSV header
+    const COLUMNS: &'static [&'static str];
+
+    /// The record's values in the order of [`Record::COLUMNS`]
+    fn values(&self) -> Vec<Option<String>>;
+}
+
+/// The records of a focused scan along with its counters
+#[derive(Debug, Clone)]
+pub struct ResultSet<T: Record> {
+    /// Heading of the listing and TXT export, such as "Interfaces"
+    pub title: &'static str,
+    pub records: Vec<T>,
+    pub stats: ScanStats,
+}
+
+/// A COM interface registered under `{root}\Interface\{iid}`
+#[derive(Debug, Clone, Serialize)]
+pub struct InterfaceInfo {
+    pub iid: String,
+    /// Default value of the interface key
+    pub name: Option<String>,
+    /// Default value of `ProxyStubClsid32`: the class that marshals the interface
+    pub proxy_stub_clsid: Option<String>,
+    /// Default value of the `TypeLib` subkey, for interfaces marshalled from a type library
+    pub type_lib: Option<String>,
+}
+
+impl Record for InterfaceInfo {
+    const COLUMNS: &'static [&'static str] = &["IID", "Name", "ProxyStubClsid32", "TypeLib"];
+
+    fn values(&self) -> Vec<Option<String>> {
+        vec![
+            Some(self.iid.clone()),
+            self.name.clone(),
+            self.proxy_stub_clsid.clone(),
+            self.type_lib.clone(),
+        ]
+    }
+}
+
+/// One shell extension registration: a handler CLSID and where Explorer picks it up
+#[derive(Debug, Clone)]
+pub struct ShellExtension {
+    /// Handler CLSID, upper-case with braces
+    pub clsid: String,
+    /// Kind of handler, such as `ContextMenuHandlers`, `IconOverlayHandler` or `Approved`
+    pub handler_type: String,
+}
+
+/// Counters collected while scanning a registry view
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ScanStats {
+    /// Number of CLSID subkeys enumerated
+    pub enumerated: usize,
+    /// Number of CLSIDs that passed all filters
+    pub matched: usize,
+    /// Number of keys that could not be opened due to `ERROR_ACCESS_DENIED`
+    pub access_denied: usize,
+    /// Number of keys that failed to open for reasons other than access denied or not found
+    pub open_errors: usize,
+    /// Number of ProgID and description values that exist but were skipped because access
+    /// to them was denied
+    #[serde(default)]
+    pub values_denied: usize,
+}
+
+impl ScanStats {
+    /// Add the counters of another scan (e.g. a second registry view)
+    pub fn add(&mut self, other: &ScanStats) {
+        self.enumerated += other.enumerated;
+        self.matched += other.matched;
+        self.access_denied += other.access_denied;
+        self.open_errors += other.open_errors;
+        self.values_denied += other.values_denied;
+    }
+}
+
+/// One registered version of a type library (`HKEY_CLASSES_ROOT\TypeLib\{guid}\{version}`)
+#[derive(Debug, Clone)]
+pub struct TypeLibVersion {
+    pub version: String,
+    pub description: Option<String>,
+    /// Default value of the `FLAGS` subkey (LIBFLAGS bitmask as a decimal string)
+    pub flags: Option<String>,
+    pub platforms: Vec<TypeLibPlatform>,
+}
+
+/// A locale/platform registration below a type library version (e.g. `0\win64`)
+#[derive(Debug, Clone)]
+pub struct TypeLibPlatform {
+    pub lcid: String,
+    pub platform: String,
+    pub path: Option<String>,
+}
+
+/// A registry key with all of its values and, recursively, its subkeys (`--dump`)
+#[derive(Debug, Clone)]
+pub struct KeyDump {
+    /// Name of the key below its parent; the full registry path for the dumped CLSID key
+    pub name: String,
+    /// False if the key is listed by its parent but could not be opened
+    pub readable: bool,
+    pub values: Vec<RawValue>,
+    pub subkeys: Vec<KeyDump>,
+}
+
+/// Name of a registry value type as in the Windows SDK, e.g. `REG_EXPAND_SZ`
+pub fn value_type_name(value_type: u32) -> String {
+    let name = match value_type {
+        0 => "REG_NONE",
+        1 => "REG_SZ",
+        2 => "REG_EXPAND_SZ",
+        3 => "REG_BINARY",
+        4 => "REG_DWORD",
+        5 => "REG_DWORD_BIG_ENDIAN",
+        6 => "REG_LINK",
+        7 => "REG_MULTI_SZ",
+        8 => "REG_RESOURCE_LIST",
+        9 => "REG_FULL_RESOURCE_DESCRIPTOR",
+        10 => "REG_RESOURCE_REQUIREMENTS_LIST",
+        11 => "REG_QWORD",
+        other => return format!("type {other}"),
+    };
+    name.to_string()
+}
+
+/// A registry value exactly as stored: its `REG_*` type code and data bytes
+#[derive(Debug, Clone)]
+pub struct RawValue {
+    /// Empty for the default value of the key
+    pub name: String,
+    pub value_type: u32,
+    pub data: Vec<u8>,
+}
+
+impl RawValue {
+    /// Name of the value type as in the Windows SDK, e.g. `REG_EXPAND_SZ`
+    pub fn type_name(&self) -> String {
+        value_type_name(self.value_type)
+    }
+
+    /// The data as text: strings quoted up to their terminator (string lists as a list), numbers
+    /// in hex and decimal, no data as `(empty)`, and everything else, including data of the
+    /// wrong size, as hex bytes
+    pub fn data_text(&self) -> String {
+        match (self.value_type, self.data.len()) {
+            (1 | 2 | 6, len) if len % 2 == 0 => {
+                format!("{:?}", self.utf16_strings().first().map_or("", String::as_str))
+            }
+            (7, len) if len % 2 == 0 => format!("{:?}", self.utf16_strings()),
+            (4, 4) => {
+                let value = u32::from_le_bytes(self.data[..4].try_into().unwrap_or_default());
+                format!("0x{value:08x} ({value})")
+            }
+            (5, 4) => {
+                let value = u32::from_be_bytes(self.data[..4].try_into().unwrap_or_default());
+                format!("0x{value:08x} ({value})")
+            }
+            (11, 8) => {
+                let value = u64::from_le_bytes(self.data[..8].try_into().unwrap_or_default());
+                format!("0x{value:016x} ({value})")
+            }
+            (_, 0) => "(empty)".to_string(),
+            _ => {
+                let bytes: Vec<String> =
+                    self.data.iter().map(|byte| format!("{byte:02x}")).collect();
+                format!("hex: {}", bytes.join(" "))
+            }
+        }
+    }
+
+    /// The data as UTF-16 strings separated by null characters, without the empty ones that
+    /// terminate the data
+    fn utf16_strings(&self) -> Vec<String> {
+        let units: Vec<u16> = self
+            .data
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        units
+            .split(|&unit| unit == 0)
+            .filter(|part| !part.is_empty())
+            .map(String::from_utf16_lossy)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn view_selection_from_every_flag_combination() {
+        let cases = [
+            ((false, false), ViewSelection::Both, true, true),
+            ((true, false), ViewSelection::Only32, true, false),
+            ((false, true), ViewSelection::Only64, false, true),
+            ((true, true), ViewSelection::Both, true, true),
+        ];
+        for ((scan_32bit, scan_64bit), expected, has_32bit, has_64bit) in cases {
+            let views = ViewSelection::from_flags(scan_32bit, scan_64bit);
+            let flags = (scan_32bit, scan_64bit);
+            assert_eq!(views, expected, "flags {flags:?}");
+            assert_eq!(views.includes_32bit(), has_32bit, "flags {flags:?}");
+            assert_eq!(views.includes_64bit(), has_64bit, "flags {flags:?}");
+        }
+    }
+
+    #[test]
+    fn options_read_their_environment_variables() {
+        use clap::CommandFactory;
+
+        let command = Args::command();
+        let options = [
+            ("filter", "OLE_INSPECTOR_FILTER"),
+            ("format", "OLE_INSPECTOR_FORMAT"),
+            ("output", "OLE_INSPECTOR_OUTPUT"),
+            ("columns", "OLE_INSPECTOR_COLUMNS"),
+            ("sort", "OLE_INSPECTOR_SORT"),
+            ("root", "OLE_INSPECTOR_ROOT"),
+            ("threads", "OLE_INSPECTOR_THREADS"),
+            ("usability_weights", "OLE_INSPECTOR_USABILITY_WEIGHTS"),
+        ];
+        for (id, variable) in options {
+            let arg = command.get_arguments().find(|arg| arg.get_id() == id).expect(id);
+            assert_eq!(arg.get_env().and_then(|env| env.to_str()), Some(variable));
+        }
+        let with_env = command.get_arguments().filter(|arg| arg.get_env().is_some()).count();
+        assert_eq!(with_env, options.len());
+    }
+
+    #[test]
+    fn objects_saved_with_only_the_original_fields_load() {
+        let json = r#"{"clsid": "{0002DF01-0000-0000-C000-000000000046}",
+            "prog_id": "InternetExplorer.Application", "description": null}"#;
+        let obj: ComObject = serde_json::from_str(json).expect("old object loads");
+        assert_eq!(obj.prog_id.as_deref(), Some("InternetExplorer.Application"));
+        assert_eq!(obj.root, RegistryRoot::ClassesRoot);
+        assert!(obj.implemented_categories.is_empty());
+        assert!(!obj.is_control);
+    }
 }
@@ -0,0 +1,267 @@
+//! Programmatic usability rating.
+//!
+//! Each factor is a signal that makes an object easier to use from code; the rating is the
+//! share of the possible points that the object's factors earn. The default weights live in
+//! [`USABILITY_FACTORS`] and are applied by [`DefaultScorer`]; `--usability-weights` loads a
+//! [`WeightedScorer`] from a JSON file such as `{"prog_id": 1, "description": 3}`. Both
+//! implement [`UsabilityScorer`], the policy the rest of the program rates objects with.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::types::{ComObject, UsabilityLevel};
+
+/// A named signal with its default weight and the check that decides whether it applies
+pub struct UsabilityFactor {
+    /// Key of the factor in a `--usability-weights` file
+    pub key: &'static str,
+    pub name: &'static str,
+    pub points: u32,
+    /// Part of the original ProgID/description heuristic kept by `--legacy-usability`
+    pub legacy: bool,
+    check: fn(&ComObject) -> bool,
+}
+
+/// Every factor of the rating with its default weight. A ProgID outweighs a description,
+/// because it lets clients instantiate the class by name, while a description only suggests
+/// that it is documented. A registered server that is present on disk is what makes the class
+/// load at all, so it weighs as much as the ProgID.
+pub const USABILITY_FACTORS: &[UsabilityFactor] = &[
+    UsabilityFactor {
+        key: "prog_id",
+        name: "has ProgID",
+        points: 2,
+        legacy: true,
+        check: |obj| obj.prog_id.is_some(),
+    },
+    UsabilityFactor {
+        key: "description",
+        name: "has description",
+        points: 1,
+        legacy: true,
+        check: |obj| obj.description.is_some(),
+    },
+    UsabilityFactor {
+        key: "server_present",
+        name: "server registered and present",
+        points: 2,
+        legacy: false,
+        check: has_present_server,
+    },
+];
+
+/// True if the class registers an `InprocServer32` or `LocalServer32` and none of them names
+/// a missing file. Bare file names are resolved through the search path and count as present.
+fn has_present_server(obj: &ComObject) -> bool {
+    has_server(obj) && !obj.server_missing
+}
+
+/// The outcome of rating an object: its level and the points behind it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsabilityRating {
+    pub level: UsabilityLevel,
+    pub points: u32,
+    pub possible: u32,
+}
+
+impl UsabilityRating {
+    /// The level for the share of the possible points an object earns: at least three
+    /// quarters is High, at least half Medium, anything above nothing Low
+    pub fn from_points(points: u32, possible: u32) -> Self {
+        let level = if points > 0 && points * 4 >= possible * 3 {
+            UsabilityLevel::High
+        } else if points > 0 && points * 2 >= possible {
+            UsabilityLevel::Medium
+        } else if points > 0 {
+            UsabilityLevel::Low
+        } else {
+            UsabilityLevel::VeryLow
+        };
+        Self {
+            level,
+            points,
+            possible,
+        }
+    }
+}
+
+/// A usability policy. The listing, every export, `--min-usability` and `--sort usability`
+/// rate objects only through this trait, so a different policy needs no other change.
+pub trait UsabilityScorer: fmt::Debug + Send + Sync {
+    fn score(&self, obj: &ComObject) -> UsabilityRating;
+
+    /// Lines explaining the rating for the `-vvv` listing; by default only the total
+    fn explain(&self, obj: &ComObject) -> Vec<String> {
+        let rating = self.score(obj);
+        vec![format!("total: {} of {}", rating.points, rating.possible)]
+    }
+
+    /// Verdict shown for a level in listings and exports
+    fn label(&self, level: UsabilityLevel) -> &'static str {
+        level.label()
+    }
+}
+
+/// The built-in heuristic: the [`USABILITY_FACTORS`] with their default weights, overruled by
+/// what is known for certain (see [`overrule`])
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultScorer;
+
+impl UsabilityScorer for DefaultScorer {
+    fn score(&self, obj: &ComObject) -> UsabilityRating {
+        overrule(obj, rate(obj, default_weights()))
+    }
+
+    fn explain(&self, obj: &ComObject) -> Vec<String> {
+        explain_factors(obj, default_weights(), true)
+    }
+}
+
+/// `--legacy-usability`: only the ProgID and description factors, with their default weights
+/// and no check of the server
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LegacyScorer;
+
+impl UsabilityScorer for LegacyScorer {
+    fn score(&self, obj: &ComObject) -> UsabilityRating {
+        rate(obj, legacy_weights())
+    }
+
+    fn explain(&self, obj: &ComObject) -> Vec<String> {
+        explain_factors(obj, legacy_weights(), false)
+    }
+
+    /// The verdicts as they read before the server check, when they also named the reason
+    fn label(&self, level: UsabilityLevel) -> &'static str {
+        match level {
+            UsabilityLevel::High => "✓ High (has ProgID and description)",
+            UsabilityLevel::Medium => "~ Medium (has ProgID)",
+            UsabilityLevel::Low => "~ Low (no ProgID, has description)",
+            UsabilityLevel::VeryLow => "✗ Very Low (no ProgID or description)",
+        }
+    }
+}
+
+/// The [`USABILITY_FACTORS`] with weights from a `--usability-weights` file, in table order
+#[derive(Debug, Clone)]
+pub struct WeightedScorer {
+    weights: Vec<u32>,
+}
+
+impl WeightedScorer {
+    /// Read a `--usability-weights` file: a JSON object mapping factor keys to integer
+    /// weights. Factors it leaves out keep their default weight; unknown keys are an error.
+    pub fn load(path: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read usability weights {path}"))?;
+        let overrides: HashMap<String, u32> = serde_json::from_str(&text)
+            .with_context(|| format!("{path} is not a JSON object of factor weights"))?;
+
+        let mut weights: Vec<u32> = default_weights().collect();
+        for (key, weight) in overrides {
+            let Some(index) = USABILITY_FACTORS.iter().position(|factor| factor.key == key) else {
+                let keys: Vec<&str> = USABILITY_FACTORS.iter().map(|factor| factor.key).collect();
+                anyhow::bail!(
+                    "Unknown usability factor {key:?} in {path} (known factors: {})",
+                    keys.join(", ")
+                );
+            };
+            weights[index] = weight;
+        }
+        Ok(Self { weights })
+    }
+}
+
+impl UsabilityScorer for WeightedScorer {
+    fn score(&self, obj: &ComObject) -> UsabilityRating {
+        overrule(obj, rate(obj, self.weights.iter().copied()))
+    }
+
+    fn explain(&self, obj: &ComObject) -> Vec<String> {
+        explain_factors(obj, self.weights.iter().copied(), true)
+    }
+}
+
+fn default_weights() -> impl Iterator<Item = u32> + Clone {
+    USABILITY_FACTORS.iter().map(|factor| factor.points)
+}
+
+/// The default weights of the legacy factors, and no weight for the others
+fn legacy_weights() -> impl Iterator<Item = u32> + Clone {
+    USABILITY_FACTORS.iter().map(|factor| if factor.legacy { factor.points } else { 0 })
+}
+
+/// Facts beat the factors: a ProgID pointing at a deleted DLL is not usable however well it is
+/// documented, and the `--probe` outcome, when there is one, decides over both. A class that
+/// COM created is High; one with a registered server that COM failed to create is Very Low.
+/// A class with a TypeLib but no ProgID is usable through early binding, so it is at least
+/// Medium.
+fn overrule(obj: &ComObject, rating: UsabilityRating) -> UsabilityRating {
+    let level = match obj.instantiable {
+        Some(true) => UsabilityLevel::High,
+        Some(false) if has_server(obj) => UsabilityLevel::VeryLow,
+        _ if obj.server_missing => UsabilityLevel::VeryLow,
+        _ if obj.has_typelib_without_prog_id() => rating.level.max(UsabilityLevel::Medium),
+        _ => rating.level,
+    };
+    UsabilityRating { level, ..rating }
+}
+
+/// The line [`explain_factors`] adds when [`overrule`] replaced `level`, the level of the factors
+fn overrule_note(obj: &ComObject, level: UsabilityLevel) -> Option<&'static str> {
+    match obj.instantiable {
+        Some(true) => Some("instantiated by --probe: rated High"),
+        Some(false) if has_server(obj) => {
+            Some("failed to instantiate despite a server: rated Very Low")
+        }
+        _ if obj.server_missing => Some("server file missing: rated Very Low"),
+        _ if obj.has_typelib_without_prog_id() && level < UsabilityLevel::Medium => {
+            Some("TypeLib without ProgID (early binding): rated Medium")
+        }
+        _ => None,
+    }
+}
+
+fn has_server(obj: &ComObject) -> bool {
+    obj.inproc_server.is_some() || obj.local_server.is_some()
+}
+
+/// Sum the weights of the factors that apply, out of the sum of all weights
+fn rate(obj: &ComObject, weights: impl Iterator<Item = u32> + Clone) -> UsabilityRating {
+    let possible = weights.clone().sum();
+    let points = USABILITY_FACTORS
+        .iter()
+        .zip(weights)
+        .filter(|(factor, _)| (factor.check)(obj))
+        .map(|(_, weight)| weight)
+        .sum();
+    UsabilityRating::from_points(points, possible)
+}
+
+/// Every factor with a weight and the points it contributed to the object's rating, followed by
+/// the total, e.g. `has ProgID: +2`, and the reason if `overruled` and [`overrule`] changed it
+fn explain_factors(
+    obj: &ComObject,
+    weights: impl Iterator<Item = u32> + Clone,
+    overruled: bool,
+) -> Vec<String> {
+    let rating = rate(obj, weights.clone());
+    let mut lines: Vec<String> = USABILITY_FACTORS
+        .iter()
+        .zip(weights)
+        .filter(|(_, weight)| *weight > 0)
+        .map(|(factor, weight)| {
+            if (factor.check)(obj) {
+                format!("{}: +{weight}", factor.name)
+            } else {
+                format!("{}: +0 (of {weight})", factor.name)
+            }
+        })
+        .collect();
+    lines.push(format!("total: {} of {}", rating.points, rating.possible));
+    if let Some(note) = overruled.then(|| overrule_note(obj, rating.level)).flatten() {
+        lines.push(note.to_string());
+    }
+    lines
+}
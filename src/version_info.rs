@@ -0,0 +1,94 @@
+//! Version resource lookup for `--check-version-info`.
+//!
+//! Most server binaries carry a `VS_VERSIONINFO` resource whose string table names the
+//! company and product. The strings are read from the first language listed under
+//! `\VarFileInfo\Translation`, falling back to US English in the Unicode code page.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use windows::core::HSTRING;
+use windows::Win32::Storage::FileSystem::{
+    GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW,
+};
+
+use crate::types::ComObject;
+
+/// Language and code page used when the resource has no translation table (US English, Unicode)
+const DEFAULT_TRANSLATION: &str = "040904b0";
+
+/// The version resource strings kept for an object's server binary
+#[derive(Debug, Clone, Default)]
+pub struct VersionInfo {
+    pub company_name: Option<String>,
+    pub product_name: Option<String>,
+}
+
+/// Sets `company_name` and `product_name` on every object from its server binary.
+/// Each binary is read once; bare file names and missing files get neither.
+pub fn check_version_info(objects: &mut HashMap<String, ComObject>) {
+    let mut versions: HashMap<PathBuf, VersionInfo> = HashMap::new();
+    for obj in objects.values_mut() {
+        let Some(path) = obj.server_binary() else {
+            continue;
+        };
+        let info = versions.entry(path).or_insert_with_key(|path| version_info(path));
+        obj.company_name = info.company_name.clone();
+        obj.product_name = info.product_name.clone();
+    }
+}
+
+/// Reads the company and product name from a file's version resource
+pub fn version_info(path: &Path) -> VersionInfo {
+    let name = HSTRING::from(path.to_string_lossy().as_ref());
+    unsafe {
+        let size = GetFileVersionInfoSizeW(&name, None);
+        if size == 0 {
+            return VersionInfo::default();
+        }
+        // u32 elements keep the buffer aligned for the resource's WORD and DWORD fields
+        let mut buffer = vec![0u32; (size as usize).div_ceil(4)];
+        if GetFileVersionInfoW(&name, 0, size, buffer.as_mut_ptr() as *mut _).is_err() {
+            return VersionInfo::default();
+        }
+
+        let block = buffer.as_ptr() as *const core::ffi::c_void;
+        let translation = translation(block).unwrap_or_else(|| DEFAULT_TRANSLATION.into());
+        VersionInfo {
+            company_name: string_value(block, &translation, "CompanyName"),
+            product_name: string_value(block, &translation, "ProductName"),
+        }
+    }
+}
+
+/// The first language and code page of the resource as the hex key of its string table
+unsafe fn translation(block: *const core::ffi::c_void) -> Option<String> {
+    let mut value: *mut core::ffi::c_void = std::ptr::null_mut();
+    let mut len = 0u32;
+    let sub_block = HSTRING::from("\\VarFileInfo\\Translation");
+    if !VerQueryValueW(block, &sub_block, &mut value, &mut len).as_bool() || len < 4 {
+        return None;
+    }
+    let language = *(value as *const u16);
+    let code_page = *(value as *const u16).add(1);
+    Some(format!("{language:04x}{code_page:04x}"))
+}
+
+/// A non-empty string from the resource's string table, without its terminating null
+unsafe fn string_value(
+    block: *const core::ffi::c_void,
+    translation: &str,
+    key: &str,
+) -> Option<String> {
+    let mut value: *mut core::ffi::c_void = std::ptr::null_mut();
+    let mut len = 0u32;
+    let sub_block = HSTRING::from(format!("\\StringFileInfo\\{translation}\\{key}"));
+    if !VerQueryValueW(block, &sub_block, &mut value, &mut len).as_bool() || len == 0 {
+        return None;
+    }
+
+    // The length is in characters and usually includes the terminating null
+    let chars = std::slice::from_raw_parts(value as *const u16, len as usize);
+    let end = chars.iter().position(|&c| c == 0).unwrap_or(chars.len());
+    let text = String::from_utf16_lossy(&chars[..end]).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}